@@ -3,10 +3,12 @@ use std::{collections::HashMap, path::PathBuf};
 use egui::{pos2, ColorImage, Pos2, Shape};
 use image::{imageops, ImageError};
 use log::{debug, error};
-use tes3::esp::{Landscape, Region};
+use tes3::esp::{Landscape, Plugin, Region};
 
 use background::{
-    gamemap::generate_map, heightmap::generate_heightmap, landscape::compute_landscape_image,
+    gamemap::{calculate_world_map_data, generate_map, generate_world_map, EWorldMapLayout},
+    heightmap::generate_heightmap,
+    landscape::compute_landscape_image,
 };
 use overlay::{paths::get_overlay_path_image, regions::get_region_shapes};
 
@@ -17,6 +19,9 @@ pub enum ESidePanelView {
     #[default]
     Plugins,
     Cells,
+    Interiors,
+    Travel,
+    Bookmarks,
     Editor,
 }
 
@@ -25,8 +30,11 @@ pub struct TooltipInfo {
     pub key: CellKey,
     pub height: f32,
     pub region: String,
+    pub region_record: Option<Region>,
     pub cell_name: String,
     pub conflicts: Vec<u64>,
+    pub reference: Option<CellReference>,
+    pub door: Option<CellReference>,
     pub debug: String,
 }
 
@@ -56,12 +64,25 @@ pub struct TemplateApp {
     pub regn_records: HashMap<String, Region>,
     #[serde(skip)]
     pub cell_records: HashMap<CellKey, Cell>,
+    #[serde(skip)]
+    pub reference_records: HashMap<CellKey, Vec<CellReference>>,
+    #[serde(skip)]
+    pub interior_records: HashMap<String, Cell>,
+    #[serde(skip)]
+    pub interior_references: HashMap<String, Vec<CellReference>>,
 
     // overlays
     #[serde(skip)]
-    pub travel_edges: HashMap<String, Vec<(CellKey, CellKey)>>,
+    pub travel_edges: HashMap<String, Vec<TravelEdge>>,
+    #[serde(skip)]
+    pub cell_issues: Vec<validation::CellIssue>,
+    #[serde(skip)]
+    pub class_names: HashMap<String, String>,
     #[serde(skip)]
     pub cell_conflicts: HashMap<CellKey, Vec<u64>>,
+    /// labels and the settings they were collected with
+    #[serde(skip)]
+    pub label_cache: Option<(LabelSettings, Vec<overlay::labels::MapLabel>)>,
     // textures in memory
     #[serde(skip)]
     pub background_handle: Option<egui::TextureHandle>,
@@ -271,6 +292,136 @@ impl TemplateApp {
         )
     }
 
+    pub fn get_travel_class_colors(&self) -> HashMap<String, Color32> {
+        let classes = self.travel_edges.keys().collect::<Vec<_>>();
+        overlay::travel::get_travel_class_colors(&classes, &self.ui_data.travel_colors)
+    }
+
+    /// Label settings with the region names of the region overlay merged in
+    pub fn get_effective_label_settings(&self) -> Option<LabelSettings> {
+        let mut settings = self.ui_data.label_settings.clone();
+        if !self.ui_data.overlay_labels {
+            settings.show_cell_names = false;
+            settings.show_region_names = false;
+        }
+        if self.ui_data.overlay_region && self.ui_data.region_settings.show_names {
+            settings.show_region_names = true;
+        }
+
+        (settings.show_cell_names || settings.show_region_names).then_some(settings)
+    }
+
+    /// Labels for the settings, only collected again after the plugins or settings changed
+    pub fn get_cached_labels(&mut self, settings: &LabelSettings) -> &[overlay::labels::MapLabel] {
+        if self
+            .label_cache
+            .as_ref()
+            .is_none_or(|(cached, _)| cached != settings)
+        {
+            let labels = overlay::labels::get_labels(
+                &self.dimensions,
+                &self.regn_records,
+                &self.cell_records,
+                settings,
+            );
+            self.label_cache = Some((settings.clone(), labels));
+        }
+        self.label_cache
+            .as_ref()
+            .map(|(_, labels)| labels.as_slice())
+            .unwrap_or_default()
+    }
+
+    /// Center the map on a cell in the next frame
+    pub fn focus_cell(&mut self, key: CellKey) {
+        let pos = self.dimensions.cell_to_canvas(key) + egui::vec2(0.5, 0.5);
+        let zoom = self.transform_data.zoom.max(4.0);
+        self.transform_data.focus = Some((pos, zoom));
+    }
+
+    /// Center the map on an engine position in the next frame
+    pub fn focus_engine(&mut self, pos: Pos2) {
+        let pos = self.dimensions.engine_to_canvas(pos);
+        let zoom = self.transform_data.zoom.max(8.0);
+        self.transform_data.focus = Some((pos, zoom));
+    }
+
+    pub fn create_bookmark(&self, name: String, with_view: bool) -> Bookmark {
+        let view_rect = self.transform_data.view_rect;
+        let center = self.dimensions.canvas_to_engine(view_rect.center());
+        let view = with_view.then(|| BookmarkView {
+            background: self.ui_data.background,
+            overlay_paths: self.ui_data.overlay_paths,
+            overlay_region: self.ui_data.overlay_region,
+            overlay_grid: self.ui_data.overlay_grid,
+            overlay_cities: self.ui_data.overlay_cities,
+            overlay_conflicts: self.ui_data.overlay_conflicts,
+            overlay_labels: self.ui_data.overlay_labels,
+            overlay_references: self.ui_data.overlay_references,
+            overlay_doors: self.ui_data.overlay_doors,
+            overlay_validation: self.ui_data.overlay_validation,
+            overlay_travel: self.ui_data.overlay_travel.clone(),
+        });
+
+        Bookmark {
+            name,
+            center: [center.x, center.y],
+            extent: [
+                view_rect.width() * CELL_WIDTH,
+                view_rect.height() * CELL_WIDTH,
+            ],
+            selected_ids: self.runtime_data.selected_ids.clone(),
+            view,
+        }
+    }
+
+    pub fn apply_bookmark(&mut self, ctx: &egui::Context, bookmark: &Bookmark) {
+        if let Some(view) = &bookmark.view {
+            self.ui_data.overlay_region = view.overlay_region;
+            self.ui_data.overlay_grid = view.overlay_grid;
+            self.ui_data.overlay_cities = view.overlay_cities;
+            self.ui_data.overlay_conflicts = view.overlay_conflicts;
+            self.ui_data.overlay_labels = view.overlay_labels;
+            self.ui_data.overlay_references = view.overlay_references;
+            self.ui_data.overlay_doors = view.overlay_doors;
+            self.ui_data.overlay_validation = view.overlay_validation;
+            // only known travel classes
+            for (class, enabled) in self.ui_data.overlay_travel.iter_mut() {
+                *enabled = view.overlay_travel.get(class).copied().unwrap_or(false);
+            }
+
+            if view.background != self.ui_data.background {
+                self.ui_data.background = view.background;
+                if self.background_handle.is_some() {
+                    self.reload_background(ctx, None, false, false);
+                }
+            }
+            if view.overlay_paths != self.ui_data.overlay_paths {
+                self.ui_data.overlay_paths = view.overlay_paths;
+                if view.overlay_paths {
+                    self.reload_paths(ctx);
+                }
+            }
+        }
+
+        self.runtime_data.interior_view = None;
+        self.runtime_data
+            .selected_ids
+            .clone_from(&bookmark.selected_ids);
+        let center = self
+            .dimensions
+            .engine_to_canvas(Pos2::new(bookmark.center[0], bookmark.center[1]));
+        // fit the stored extent into the current viewport
+        let view_size = self.transform_data.view_rect.size();
+        let extent = egui::vec2(bookmark.extent[0], bookmark.extent[1]) / CELL_WIDTH;
+        let zoom = if extent.min_elem() > 0.0 && view_size.min_elem() > 0.0 {
+            self.transform_data.zoom * (view_size.x / extent.x).min(view_size.y / extent.y)
+        } else {
+            self.transform_data.zoom
+        };
+        self.transform_data.focus = Some((center, zoom));
+    }
+
     // UI methods
     pub fn reset_zoom(&mut self) {
         self.transform_data.zoom = 1.0;
@@ -282,6 +433,88 @@ impl TemplateApp {
         self.transform_data.drag_start = Pos2::default();
     }
 
+    /// Export the world map texture in the layout of the given engine
+    pub fn export_world_map(&mut self, layout: EWorldMapLayout) -> Result<(), ImageError> {
+        let (extension, defaultname) = match layout {
+            EWorldMapLayout::Morrowind => ("dds", "map.dds"),
+            EWorldMapLayout::OpenMW => ("png", "globalmap.png"),
+        };
+
+        let file_option = rfd::FileDialog::new()
+            .add_filter(extension, &[extension])
+            .set_file_name(defaultname)
+            .save_file();
+
+        if let Some(path) = file_option {
+            let image = generate_world_map(layout, &self.dimensions, &self.land_records);
+            match layout {
+                EWorldMapLayout::Morrowind => {
+                    save_color_image_as_dds(&image, path).map_err(ImageError::IoError)?
+                }
+                EWorldMapLayout::OpenMW => color_image_to_dynamic_image(&image)?.save(path)?,
+            }
+
+            rfd::MessageDialog::new()
+                .set_title("Info")
+                .set_description("World map saved successfully")
+                .set_buttons(rfd::MessageButtons::Ok)
+                .show();
+        }
+
+        Ok(())
+    }
+
+    /// Recalculate the WNAM data of all LAND records in a plugin and save the result
+    pub fn regenerate_world_map_data(&mut self) -> std::io::Result<()> {
+        let mut dialog = rfd::FileDialog::new().add_filter("Plugins", &["esm", "esp"]);
+        if let Some(data_files) = &self.data_files {
+            dialog = dialog.set_directory(data_files);
+        }
+        let Some(input_path) = dialog.pick_file() else {
+            return Ok(());
+        };
+
+        let mut plugin = Plugin::new();
+        plugin.load_path(&input_path)?;
+
+        // WNAM is covered by the same LAND data flag as the vertex heights and normals,
+        // records without heights are skipped so no flag needs to be set
+        let mut count = 0;
+        for land in plugin.objects_of_type_mut::<Landscape>() {
+            if let Some(data) = calculate_world_map_data(land) {
+                *land.world_map_data.data = data;
+                count += 1;
+            }
+        }
+
+        let file_name = input_path.file_name().unwrap_or_default().to_owned();
+        let file_option = rfd::FileDialog::new()
+            .add_filter("Plugins", &["esm", "esp"])
+            .set_directory(input_path.parent().unwrap_or(Path::new(".")))
+            .set_file_name(file_name.to_string_lossy())
+            .save_file();
+
+        if let Some(output_path) = file_option {
+            plugin.save_path(&output_path)?;
+            info!(
+                "Regenerated world map data for {} LAND records in {}",
+                count,
+                output_path.display()
+            );
+
+            rfd::MessageDialog::new()
+                .set_title("Info")
+                .set_description(format!(
+                    "Regenerated world map data for {} LAND records",
+                    count
+                ))
+                .set_buttons(rfd::MessageButtons::Ok)
+                .show();
+        }
+
+        Ok(())
+    }
+
     pub fn save_image(&mut self, ctx: &egui::Context) -> Result<(), ImageError> {
         // construct default name from the first plugin name then the background type abbreviated
         let background_name = match self.ui_data.background {
@@ -361,7 +594,11 @@ impl TemplateApp {
                     || self.ui_data.overlay_grid
                     || self.ui_data.overlay_cities
                     || self.ui_data.overlay_travel.values().any(|v| *v)
-                    || self.ui_data.overlay_conflicts;
+                    || self.ui_data.overlay_conflicts
+                    || self.ui_data.overlay_labels
+                    || self.ui_data.overlay_references
+                    || self.ui_data.overlay_doors
+                    || self.ui_data.overlay_validation;
 
                 if any_overlay {
                     let real_width = self.dimensions.width() as f32;
@@ -378,7 +615,7 @@ impl TemplateApp {
 
                     let mut all_shapes = vec![];
 
-                    // order is: paths, regions, grid, cities, travel, conflicts
+                    // order is: paths, regions, grid, cities, references, doors, travel, validation, conflicts, labels
                     // regions
                     if self.ui_data.overlay_region {
                         let shapes = get_region_shapes(
@@ -386,6 +623,7 @@ impl TemplateApp {
                             &self.dimensions,
                             &self.regn_records,
                             &self.cell_records,
+                            &self.ui_data.region_settings,
                         );
                         all_shapes.extend(shapes);
                     }
@@ -403,7 +641,31 @@ impl TemplateApp {
                         );
                         all_shapes.extend(shapes);
                     }
+                    // references
+                    if self.ui_data.overlay_references {
+                        let shapes = overlay::references::get_reference_shapes(
+                            transform,
+                            &self.dimensions,
+                            &self.reference_records,
+                            &self.ui_data.reference_filters,
+                            *transform.to(),
+                            1.0,
+                        );
+                        all_shapes.extend(shapes);
+                    }
+                    // doors
+                    if self.ui_data.overlay_doors {
+                        let shapes = overlay::doors::get_door_shapes(
+                            transform,
+                            &self.dimensions,
+                            &self.reference_records,
+                            &self.interior_records,
+                            1.0,
+                        );
+                        all_shapes.extend(shapes);
+                    }
                     // travel
+                    let travel_colors = self.get_travel_class_colors();
                     for class in self.travel_edges.keys() {
                         if let Some(class_option) = self.ui_data.overlay_travel.get(class) {
                             if *class_option {
@@ -412,12 +674,24 @@ impl TemplateApp {
                                     &self.dimensions,
                                     &self.travel_edges,
                                     class,
+                                    travel_colors[class],
+                                    None,
                                 );
                                 all_shapes.extend(shapes);
                             }
                         }
                     }
 
+                    // validation
+                    if self.ui_data.overlay_validation {
+                        let shapes = overlay::validation::get_validation_shapes(
+                            transform,
+                            &self.dimensions,
+                            &self.cell_issues,
+                        );
+                        all_shapes.extend(shapes);
+                    }
+
                     // conflicts
                     if self.ui_data.overlay_conflicts {
                         let shapes = overlay::conflicts::get_conflict_shapes(
@@ -428,6 +702,24 @@ impl TemplateApp {
                         all_shapes.extend(shapes);
                     }
 
+                    // labels
+                    if let Some(label_settings) = self.get_effective_label_settings() {
+                        let labels = self.get_cached_labels(&label_settings);
+                        let shapes = ctx.fonts(|fonts| {
+                            overlay::labels::get_label_shapes(
+                                fonts,
+                                transform,
+                                labels,
+                                &label_settings,
+                                1.0,
+                            )
+                        });
+                        all_shapes.extend(shapes);
+                    }
+
+                    // the glyphs are only in the atlas after layout
+                    let font_atlas = ctx.fonts(|fonts| fonts.image());
+
                     // draw the shapes
                     for shape in all_shapes {
                         match shape {
@@ -448,12 +740,7 @@ impl TemplateApp {
                                                 || y < stroke_width as u32
                                                 || y > (rect.height() - stroke_width) as u32
                                             {
-                                                image::Rgba([
-                                                    color.r(),
-                                                    color.g(),
-                                                    color.b(),
-                                                    color.a(),
-                                                ])
+                                                image::Rgba(color.to_srgba_unmultiplied())
                                             } else {
                                                 image::Rgba([0, 0, 0, 0])
                                             }
@@ -474,7 +761,7 @@ impl TemplateApp {
                                     let img = ImageBuffer::from_pixel(
                                         rect.width() as u32,
                                         rect.height() as u32,
-                                        image::Rgba([color.r(), color.g(), color.b(), color.a()]),
+                                        image::Rgba(color.to_srgba_unmultiplied()),
                                     );
                                     imageops::overlay(
                                         &mut bg_image,
@@ -484,38 +771,32 @@ impl TemplateApp {
                                     );
                                 }
                             }
-                            // TODO fix lines
                             Shape::LineSegment { points, stroke } => {
-                                let stroke_width = stroke.width;
-                                for i in 0..points.len() - 1 {
-                                    let p1 = points[i];
-                                    let p2 = points[i + 1];
-
-                                    let img = ImageBuffer::from_fn(
-                                        (p1.distance(p2) + 1.0) as u32,
-                                        stroke_width as u32,
-                                        |x, _y| {
-                                            if x < stroke_width as u32 {
-                                                image::Rgba([
-                                                    stroke.color.r(),
-                                                    stroke.color.g(),
-                                                    stroke.color.b(),
-                                                    stroke.color.a(),
-                                                ])
-                                            } else {
-                                                image::Rgba([0, 0, 0, 0])
-                                            }
-                                        },
-                                    );
-
-                                    let min = Pos2::new(p1.x.min(p2.x), p1.y.min(p2.y));
-                                    imageops::overlay(
-                                        &mut bg_image,
-                                        &img,
-                                        min.x as i64,
-                                        min.y as i64,
-                                    );
-                                }
+                                rasterize_line(&mut bg_image, points, stroke);
+                            }
+                            Shape::Circle(circle) => {
+                                let color = circle.fill;
+                                let radius = circle.radius.max(1.0);
+                                let size = (2.0 * radius).ceil() as u32;
+
+                                let img = ImageBuffer::from_fn(size, size, |x, y| {
+                                    let d = Pos2::new(x as f32 + 0.5, y as f32 + 0.5)
+                                        .distance(Pos2::new(radius, radius));
+                                    if d <= radius {
+                                        image::Rgba(color.to_srgba_unmultiplied())
+                                    } else {
+                                        image::Rgba([0, 0, 0, 0])
+                                    }
+                                });
+                                imageops::overlay(
+                                    &mut bg_image,
+                                    &img,
+                                    (circle.center.x - radius) as i64,
+                                    (circle.center.y - radius) as i64,
+                                );
+                            }
+                            Shape::Text(text) => {
+                                overlay::labels::rasterize_text(&mut bg_image, &text, &font_atlas);
                             }
                             _ => {}
                         }
@@ -1,18 +1,41 @@
 use std::collections::HashMap;
 
 use egui::{Color32, ColorImage};
-use tes3::esp::Landscape;
+use tes3::esp::{Landscape, LandscapeFlags};
 
-use crate::{CellKey, Dimensions};
+use crate::{decode_heights, CellKey, Dimensions, VERTEX_CNT};
 
 static GRID: usize = 9;
 
+/// Morrowind's world map texture is 512x512 pixels with 9 pixels per cell
+static MORROWIND_MAP_SIZE: usize = 512;
+static MORROWIND_MAP_BOUNDS: (i32, i32) = (-28, 27);
+/// OpenMW's default "global map cell size"
+static OPENMW_CELL_SIZE: usize = 18;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EWorldMapLayout {
+    /// fixed 512x512 texture centered on cell (0,0), like the vanilla map
+    Morrowind,
+    /// all loaded cells, upscaled like the OpenMW global map
+    OpenMW,
+}
+
 pub fn generate_map(
     dimensions: &Dimensions,
     landscape_records: &HashMap<CellKey, Landscape>,
 ) -> ColorImage {
-    let height = dimensions.pixel_height(GRID);
-    let width = dimensions.pixel_width(GRID);
+    generate_map_with_cell_size(dimensions, landscape_records, GRID)
+}
+
+/// Generate the map with `cell_size` pixels per cell, upscaling the 9x9 WNAM grid if necessary
+pub fn generate_map_with_cell_size(
+    dimensions: &Dimensions,
+    landscape_records: &HashMap<CellKey, Landscape>,
+    cell_size: usize,
+) -> ColorImage {
+    let height = dimensions.pixel_height(cell_size);
+    let width = dimensions.pixel_width(cell_size);
     let size = height * width;
 
     // calculate map size
@@ -21,17 +44,17 @@ pub fn generate_map(
     for grid_y in 0..height {
         for grid_x in (0..width).rev() {
             // we can divide by grid to get the cell and subtract the bounds to get the cell coordinates
-            let x = (grid_x / GRID) as i32 + dimensions.min_x;
-            let y = (grid_y / GRID) as i32 + dimensions.min_y;
+            let x = (grid_x / cell_size) as i32 + dimensions.min_x;
+            let y = (grid_y / cell_size) as i32 + dimensions.min_y;
 
             // get LAND record
             let key = (x, y);
             if let Some(land) = landscape_records.get(&key) {
                 // get remainder
-                let hx = grid_x % GRID;
-                let hy = grid_y % GRID;
+                let hx = (grid_x % cell_size) * GRID / cell_size;
+                let hy = (grid_y % cell_size) * GRID / cell_size;
 
-                let heightmap = land.world_map_data.data.clone().to_vec();
+                let heightmap = &land.world_map_data.data;
                 pixels.push(get_map_color(heightmap[hy][hx] as f32));
             } else {
                 pixels.push(Color32::TRANSPARENT);
@@ -44,6 +67,67 @@ pub fn generate_map(
     ColorImage::new([width, height], pixels)
 }
 
+/// Generate the world map at the resolution and layout the engine expects
+pub fn generate_world_map(
+    layout: EWorldMapLayout,
+    dimensions: &Dimensions,
+    landscape_records: &HashMap<CellKey, Landscape>,
+) -> ColorImage {
+    match layout {
+        EWorldMapLayout::Morrowind => {
+            let (min, max) = MORROWIND_MAP_BOUNDS;
+            let bounds = Dimensions {
+                min_x: min,
+                min_y: min,
+                max_x: max,
+                max_y: max,
+                min_z: dimensions.min_z,
+                max_z: dimensions.max_z,
+            };
+            let map = generate_map(&bounds, landscape_records);
+
+            // pad to the texture size, the cells start at the top left corner
+            let mut image = ColorImage::filled(
+                [MORROWIND_MAP_SIZE, MORROWIND_MAP_SIZE],
+                Color32::TRANSPARENT,
+            );
+            for y in 0..map.height().min(MORROWIND_MAP_SIZE) {
+                for x in 0..map.width().min(MORROWIND_MAP_SIZE) {
+                    image[(x, y)] = map[(x, y)];
+                }
+            }
+            image
+        }
+        EWorldMapLayout::OpenMW => {
+            generate_map_with_cell_size(dimensions, landscape_records, OPENMW_CELL_SIZE)
+        }
+    }
+}
+
+/// Calculate the 9x9 WNAM grid from the vertex heights of a LAND record
+pub fn calculate_world_map_data(landscape: &Landscape) -> Option<[[i8; 9]; 9]> {
+    if !landscape
+        .landscape_flags
+        .contains(LandscapeFlags::USES_VERTEX_HEIGHTS_AND_NORMALS)
+    {
+        return None;
+    }
+
+    let heights = decode_heights(landscape);
+    let step = (VERTEX_CNT - 1) / (GRID - 1);
+
+    let mut data = [[0_i8; 9]; 9];
+    for (y, row) in data.iter_mut().enumerate() {
+        for (x, value) in row.iter_mut().enumerate() {
+            // inverse of get_map_color: one WNAM unit covers 128 engine units
+            let h = heights[y * step][x * step] / 128.0;
+            *value = h.round().clamp(i8::MIN as f32, i8::MAX as f32) as i8;
+        }
+    }
+
+    Some(data)
+}
+
 /// https://github.com/NullCascade/morrowind-mods/blob/master/User%20Interface%20Expansion/plugin_source/PatchWorldMap.cpp#L158
 fn get_map_color(h: f32) -> Color32 {
     #[derive(Default)]
@@ -91,3 +175,46 @@ fn get_map_color(h: f32) -> Color32 {
         pixel_color.b as u8,
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use tes3::esp::VertexHeights;
+
+    use super::*;
+
+    fn landscape(offset: f32, row_step: i8) -> Landscape {
+        let mut data = Box::new([[0_i8; 65]; 65]);
+        for row in data.iter_mut().skip(1) {
+            row[0] = row_step;
+        }
+        Landscape {
+            landscape_flags: LandscapeFlags::USES_VERTEX_HEIGHTS_AND_NORMALS,
+            vertex_heights: VertexHeights { offset, data },
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn world_map_data_from_heights() {
+        // 160 * 8 = 1280 engine units, rising 128 units per vertex row
+        let data = calculate_world_map_data(&landscape(160.0, 16)).unwrap();
+        assert_eq!(data[0], [10; 9]);
+        assert_eq!(data[1], [18; 9]);
+        assert_eq!(data[8], [74; 9]);
+    }
+
+    #[test]
+    fn world_map_data_is_clamped() {
+        let data = calculate_world_map_data(&landscape(-20000.0, 0)).unwrap();
+        assert_eq!(data[4][4], i8::MIN);
+        let data = calculate_world_map_data(&landscape(20000.0, 0)).unwrap();
+        assert_eq!(data[4][4], i8::MAX);
+    }
+
+    #[test]
+    fn world_map_data_needs_heights() {
+        let mut land = landscape(160.0, 0);
+        land.landscape_flags = LandscapeFlags::empty();
+        assert!(calculate_world_map_data(&land).is_none());
+    }
+}
@@ -1,6 +1,9 @@
 use std::env;
 
+use log::error;
+
 use crate::app::ESidePanelView;
+use crate::background::gamemap::EWorldMapLayout;
 use crate::*;
 
 const VERSION: &str = env!("CARGO_PKG_VERSION");
@@ -21,6 +24,47 @@ impl eframe::App for TemplateApp {
             // The top panel is often a good place for a menu bar:
             egui::MenuBar::new().ui(ui, |ui| {
                 ui.menu_button("File", |ui| {
+                    ui.menu_button("Export world map", |ui| {
+                        if ui.button("Morrowind (map.dds)").clicked() {
+                            if let Err(e) = self.export_world_map(EWorldMapLayout::Morrowind) {
+                                error!("Error exporting world map: {:?}", e);
+                                rfd::MessageDialog::new()
+                                    .set_title("Error")
+                                    .set_description(format!("Error exporting world map: {}", e))
+                                    .set_buttons(rfd::MessageButtons::Ok)
+                                    .show();
+                            }
+                            ui.close_kind(egui::UiKind::Menu);
+                        }
+                        if ui.button("OpenMW (global map)").clicked() {
+                            if let Err(e) = self.export_world_map(EWorldMapLayout::OpenMW) {
+                                error!("Error exporting world map: {:?}", e);
+                                rfd::MessageDialog::new()
+                                    .set_title("Error")
+                                    .set_description(format!("Error exporting world map: {}", e))
+                                    .set_buttons(rfd::MessageButtons::Ok)
+                                    .show();
+                            }
+                            ui.close_kind(egui::UiKind::Menu);
+                        }
+                    });
+                    if ui.button("Regenerate world map data (WNAM)").clicked() {
+                        if let Err(e) = self.regenerate_world_map_data() {
+                            error!("Error regenerating world map data: {:?}", e);
+                            rfd::MessageDialog::new()
+                                .set_title("Error")
+                                .set_description(format!(
+                                    "Error regenerating world map data: {}",
+                                    e
+                                ))
+                                .set_buttons(rfd::MessageButtons::Ok)
+                                .show();
+                        }
+                        ui.close_kind(egui::UiKind::Menu);
+                    }
+
+                    ui.separator();
+
                     if ui.button("Quit").clicked() {
                         ctx.send_viewport_cmd(egui::ViewportCommand::Close);
                     }
@@ -52,6 +96,17 @@ impl eframe::App for TemplateApp {
                     egui::widgets::global_theme_preference_buttons(ui);
                     ui.label("Theme:");
                     ui.add_space(32.0);
+
+                    // global search
+                    if ui.small_button("x").clicked() {
+                        self.runtime_data.search_query.clear();
+                    }
+                    ui.add(
+                        egui::TextEdit::singleline(&mut self.runtime_data.search_query)
+                            .hint_text("Cell, region, x,y or id")
+                            .desired_width(200.0),
+                    );
+                    ui.label("Search:");
                 });
             });
         });
@@ -66,6 +121,17 @@ impl eframe::App for TemplateApp {
                     "Plugins",
                 );
                 ui.selectable_value(&mut self.side_panel_view, ESidePanelView::Cells, "Cells");
+                ui.selectable_value(
+                    &mut self.side_panel_view,
+                    ESidePanelView::Interiors,
+                    "Interiors",
+                );
+                ui.selectable_value(&mut self.side_panel_view, ESidePanelView::Travel, "Travel");
+                ui.selectable_value(
+                    &mut self.side_panel_view,
+                    ESidePanelView::Bookmarks,
+                    "Bookmarks",
+                );
 
                 // only show editor tab if enabled
                 if self.editor_data.enabled {
@@ -81,6 +147,9 @@ impl eframe::App for TemplateApp {
                 // view
                 app::ESidePanelView::Plugins => self.plugins_panel(ui, ctx),
                 app::ESidePanelView::Cells => self.cell_panel(ui, ctx),
+                app::ESidePanelView::Interiors => self.interiors_panel(ui, ctx),
+                app::ESidePanelView::Travel => self.travel_panel(ui, ctx),
+                app::ESidePanelView::Bookmarks => self.bookmarks_panel(ui, ctx),
                 app::ESidePanelView::Editor => self.editor_panel(ui, ctx),
             }
         });
@@ -110,8 +179,49 @@ impl eframe::App for TemplateApp {
         });
 
         egui::CentralPanel::default().show(ctx, |ui| {
-            self.map_panel(ui, ctx);
+            if self.runtime_data.interior_view.is_some() {
+                self.interior_panel(ui, ctx);
+            } else {
+                self.map_panel(ui, ctx);
+            }
         });
+
+        self.editor_shortcuts(ctx);
+        self.editor_update_playback(ctx);
+
+        // warn about unsaved editor changes
+        if ctx.input(|i| i.viewport().close_requested())
+            && !self.runtime_data.close_confirmed
+            && self.editor_data.is_dirty()
+        {
+            match rfd::MessageDialog::new()
+                .set_title("Unsaved changes")
+                .set_description("Save the editor changes before closing?")
+                .set_buttons(rfd::MessageButtons::YesNoCancel)
+                .show()
+            {
+                rfd::MessageDialogResult::Yes => {
+                    self.editor_data.save_all();
+                    if !self.editor_data.io_errors.is_empty() {
+                        // keep the app open to show the errors
+                        ctx.send_viewport_cmd(egui::ViewportCommand::CancelClose);
+                    }
+                }
+                rfd::MessageDialogResult::No => {
+                    self.runtime_data.close_confirmed = true;
+                }
+                _ => {
+                    ctx.send_viewport_cmd(egui::ViewportCommand::CancelClose);
+                }
+            }
+        }
+
+        // windows
+        self.doors_window(ctx);
+        self.travel_window(ctx);
+        self.search_window(ctx);
+        self.measure_window(ctx);
+        self.profile_window(ctx);
     }
 
     /// Called by the frame work to save state before shutdown.
@@ -6,16 +6,17 @@ use std::{
     path::{Path, PathBuf},
 };
 
-use egui::{emath::RectTransform, Color32, ColorImage, Pos2, Rect};
+use egui::{emath::RectTransform, emath::Rot2, Color32, ColorImage, Pos2, Rect, Shape, Stroke};
 use image::{
     error::{ImageFormatHint, UnsupportedError, UnsupportedErrorKind},
-    DynamicImage, ImageError, RgbaImage,
+    DynamicImage, ImageError, Pixel, RgbaImage,
 };
 use log::{info, warn};
 use seahash::hash;
 use serde::{Deserialize, Serialize};
 use tes3::esp::{
-    Cell, EditorId, Landscape, LandscapeFlags, LandscapeTexture, TES3Object, TypeInfo,
+    Cell, EditorId, Landscape, LandscapeFlags, LandscapeTexture, TES3Object, TravelDestination,
+    TypeInfo,
 };
 
 pub use app::TemplateApp;
@@ -28,6 +29,9 @@ mod background;
 mod dimensions;
 mod eframe_app;
 mod overlay;
+mod pathfinding;
+mod travel_graph;
+mod validation;
 mod views;
 
 const GRID_SIZE: usize = 16;
@@ -47,6 +51,72 @@ pub enum EBackground {
     GameMap,
 }
 
+/// The base record type of a cell reference
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum ERefType {
+    Npc,
+    Creature,
+    Container,
+    Door,
+    Light,
+    Activator,
+    Static,
+    Other,
+}
+
+impl ERefType {
+    pub const ALL: [ERefType; 8] = [
+        ERefType::Npc,
+        ERefType::Creature,
+        ERefType::Container,
+        ERefType::Door,
+        ERefType::Light,
+        ERefType::Activator,
+        ERefType::Static,
+        ERefType::Other,
+    ];
+}
+
+/// A placed object in an exterior cell
+#[derive(Debug, Clone, PartialEq)]
+pub struct CellReference {
+    pub id: String,
+    /// lowercase file name of the plugin the reference was placed in
+    pub source: String,
+    pub refr_index: u32,
+    pub position: [f32; 3],
+    pub rotation: [f32; 3],
+    pub scale: f32,
+    pub ref_type: ERefType,
+    /// door destination
+    pub destination: Option<TravelDestination>,
+}
+
+/// A travel service from an NPC to one of its destinations
+#[derive(Debug, Clone, PartialEq)]
+pub struct TravelEdge {
+    pub npc_id: String,
+    pub npc_name: String,
+    pub class: String,
+    /// position of the NPC reference, the door out of the interior for NPCs in interiors
+    pub start: [f32; 3],
+    /// exterior position, the door out of the interior for interior destinations
+    pub destination: [f32; 3],
+    /// interior cell the service travels to
+    pub interior: Option<String>,
+    /// interior cell the NPC is in
+    pub npc_interior: Option<String>,
+}
+
+impl TravelEdge {
+    pub fn start_cell(&self) -> CellKey {
+        cell_key_from_engine(self.start[0], self.start[1])
+    }
+    pub fn destination_cell(&self) -> CellKey {
+        cell_key_from_engine(self.destination[0], self.destination[1])
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 pub struct LandscapeSettings {
     pub texture_size: usize, // landscape
@@ -96,7 +166,103 @@ impl Default for HeightmapSettings {
     }
 }
 
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct LabelSettings {
+    pub font_size: f32,
+    pub halo: bool,
+    pub scale_with_zoom: bool,
+    pub show_cell_names: bool,
+    pub show_region_names: bool,
+}
+
+impl Default for LabelSettings {
+    fn default() -> Self {
+        Self {
+            font_size: 12.0,
+            halo: true,
+            scale_with_zoom: true,
+            show_cell_names: true,
+            show_region_names: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct RegionSettings {
+    pub opacity: f32,
+    pub outlines_only: bool,
+    pub show_names: bool,
+}
+
+impl Default for RegionSettings {
+    fn default() -> Self {
+        Self {
+            opacity: 0.6,
+            outlines_only: false,
+            show_names: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize, Serialize)]
+pub enum EMinimapCorner {
+    TopLeft,
+    #[default]
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct MinimapSettings {
+    pub enabled: bool,
+    /// size of the longer side in points
+    pub size: f32,
+    pub corner: EMinimapCorner,
+}
+
+impl Default for MinimapSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            size: 200.0,
+            corner: EMinimapCorner::TopRight,
+        }
+    }
+}
+
+/// Background and overlays stored with a bookmark
+#[derive(Debug, Clone, PartialEq, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct BookmarkView {
+    pub background: EBackground,
+    pub overlay_paths: bool,
+    pub overlay_region: bool,
+    pub overlay_grid: bool,
+    pub overlay_cities: bool,
+    pub overlay_conflicts: bool,
+    pub overlay_labels: bool,
+    pub overlay_references: bool,
+    pub overlay_doors: bool,
+    pub overlay_validation: bool,
+    pub overlay_travel: HashMap<String, bool>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct Bookmark {
+    pub name: String,
+    /// map center in engine coordinates
+    pub center: [f32; 2],
+    /// visible width and height in engine units, the zoom depends on the viewport size
+    #[serde(default)]
+    pub extent: [f32; 2],
+    #[serde(default)]
+    pub selected_ids: Vec<CellKey>,
+    pub view: Option<BookmarkView>,
+}
+
 #[derive(Debug, Clone, PartialEq, Default, Deserialize, Serialize)]
+#[serde(default)]
 pub struct SavedData {
     // background
     // you can only have one background
@@ -106,10 +272,17 @@ pub struct SavedData {
     // you can have multiple overlays
     pub overlay_paths: bool,
     pub overlay_region: bool,
+    pub region_settings: RegionSettings,
     pub overlay_grid: bool,
     pub overlay_cities: bool,
     pub overlay_conflicts: bool,
-    pub overlay_travel: HashMap<String, bool>, // travel class
+    pub overlay_labels: bool,
+    pub overlay_references: bool,
+    pub overlay_doors: bool,
+    pub overlay_validation: bool,
+    pub reference_filters: HashMap<ERefType, bool>, // hidden if false
+    pub overlay_travel: HashMap<String, bool>,      // travel class
+    pub travel_colors: HashMap<String, Color32>,    // user overrides per travel class
 
     pub show_tooltips: bool,
 
@@ -118,16 +291,47 @@ pub struct SavedData {
     // settings
     pub landscape_settings: LandscapeSettings,
     pub heightmap_settings: HeightmapSettings,
+    pub label_settings: LabelSettings,
+    pub minimap_settings: MinimapSettings,
+
+    pub bookmarks: Vec<Bookmark>,
 }
 
 #[derive(Debug, Clone, Default)]
 pub struct RuntimeData {
     pub plugin_filter: String,
     pub cell_filter: String,
+    pub interior_filter: String,
+    pub search_query: String,
+    /// results of the last search and the query they were found for
+    pub search_results: Option<(String, Vec<views::search::SearchResult>)>,
+    pub bookmark_name: String,
+    pub bookmark_view: bool,
+    /// measurement points in engine coordinates
+    pub measure_mode: bool,
+    pub measure_points: Vec<Pos2>,
+    pub measure_closed: bool,
+    pub profile_open: bool,
+    pub profile_source: views::profile_view::EProfileSource,
+    pub profile_mode: views::profile_view::ETravelMode,
+    /// closing with unsaved editor changes was confirmed
+    pub close_confirmed: bool,
 
     pub info: TooltipInfo,
 
     pub selected_ids: Vec<CellKey>,
+    /// interior whose doors are listed in the doors window
+    pub selected_interior: Option<String>,
+    /// interior shown instead of the map
+    pub interior_view: Option<views::interior_view::InteriorView>,
+    /// travel edge shown in the travel window
+    pub selected_travel_edge: Option<TravelEdge>,
+    /// travel network of the loaded plugins and its one-way connections and dead ends
+    pub travel_graph: travel_graph::TravelGraph,
+    pub travel_one_way: Vec<travel_graph::GraphEdge>,
+    pub travel_without_incoming: Vec<String>,
+    pub travel_from: String,
+    pub travel_to: String,
     pub pivot_id: Option<CellKey>,
     pub hover_pos: CellKey,
 }
@@ -140,6 +344,11 @@ pub struct TransformData {
 
     zoom: f32,
     zoom_delta: Option<f32>,
+
+    /// canvas position and zoom to center the map on in the next frame
+    focus: Option<(Pos2, f32)>,
+    /// canvas area visible in the map viewport
+    view_rect: Rect,
 }
 
 impl Default for TransformData {
@@ -150,6 +359,8 @@ impl Default for TransformData {
             drag_offset: Default::default(),
             zoom: 1.0,
             zoom_delta: Default::default(),
+            focus: Default::default(),
+            view_rect: Rect::NOTHING,
         }
     }
 }
@@ -257,6 +468,61 @@ fn color_image_to_dynamic_image(color_image: &ColorImage) -> Result<DynamicImage
     }
 }
 
+/// Write an uncompressed 32-bit BGRA DDS file, the format Morrowind reads without conversion.
+fn save_color_image_as_dds<P>(color_image: &ColorImage, path: P) -> std::io::Result<()>
+where
+    P: AsRef<Path>,
+{
+    const DDSD_CAPS: u32 = 0x1;
+    const DDSD_HEIGHT: u32 = 0x2;
+    const DDSD_WIDTH: u32 = 0x4;
+    const DDSD_PITCH: u32 = 0x8;
+    const DDSD_PIXELFORMAT: u32 = 0x1000;
+    const DDPF_ALPHAPIXELS: u32 = 0x1;
+    const DDPF_RGB: u32 = 0x40;
+    const DDSCAPS_TEXTURE: u32 = 0x1000;
+
+    let width = color_image.width() as u32;
+    let height = color_image.height() as u32;
+
+    let mut header: Vec<u32> = vec![
+        124,
+        DDSD_CAPS | DDSD_HEIGHT | DDSD_WIDTH | DDSD_PITCH | DDSD_PIXELFORMAT,
+        height,
+        width,
+        width * 4,
+        0,
+        0,
+    ];
+    // reserved
+    header.extend([0; 11]);
+    // pixel format
+    header.extend([
+        32,
+        DDPF_RGB | DDPF_ALPHAPIXELS,
+        0,
+        32,
+        0x00ff_0000,
+        0x0000_ff00,
+        0x0000_00ff,
+        0xff00_0000,
+    ]);
+    // caps
+    header.extend([DDSCAPS_TEXTURE, 0, 0, 0, 0]);
+
+    let mut bytes: Vec<u8> = Vec::with_capacity(128 + color_image.pixels.len() * 4);
+    bytes.extend_from_slice(b"DDS ");
+    for value in header {
+        bytes.extend_from_slice(&value.to_le_bytes());
+    }
+    for color in &color_image.pixels {
+        let [r, g, b, a] = color.to_srgba_unmultiplied();
+        bytes.extend_from_slice(&[b, g, r, a]);
+    }
+
+    fs::write(path, bytes)
+}
+
 fn calculate_dimensions(
     dimensions: &Dimensions,
     landscape_records: &HashMap<CellKey, Landscape>,
@@ -374,30 +640,10 @@ pub fn calculate_heights(
                     .landscape_flags
                     .contains(LandscapeFlags::USES_VERTEX_HEIGHTS_AND_NORMALS)
                 {
-                    // get vertex data
-                    // get data
-                    let data = &landscape.vertex_heights.data;
-                    let mut heights: [[f32; 65]; 65] = [[0.0; VERTEX_CNT]; VERTEX_CNT];
-                    for y in 0..VERTEX_CNT {
-                        for x in 0..VERTEX_CNT {
-                            heights[y][x] = data[y][x] as f32;
-                        }
-                    }
-
-                    // decode
-                    let mut offset: f32 = landscape.vertex_heights.offset;
-                    for row in heights.iter_mut().take(VERTEX_CNT) {
-                        for x in row.iter_mut().take(VERTEX_CNT) {
-                            offset += *x;
-                            *x = offset;
-                        }
-                        offset = row[0];
-                    }
+                    let heights = decode_heights(landscape);
 
-                    for row in &mut heights {
+                    for row in &heights {
                         for height in row {
-                            *height *= 8.0;
-
                             let z = *height;
                             if let Some(minz) = min_z {
                                 if z < minz {
@@ -432,6 +678,36 @@ pub fn calculate_heights(
     Some(heights)
 }
 
+/// Decode the delta-encoded vertex heights of a LAND record into engine units.
+pub fn decode_heights(landscape: &Landscape) -> [[f32; 65]; 65] {
+    // get vertex data
+    let data = &landscape.vertex_heights.data;
+    let mut heights: [[f32; 65]; 65] = [[0.0; VERTEX_CNT]; VERTEX_CNT];
+    for y in 0..VERTEX_CNT {
+        for x in 0..VERTEX_CNT {
+            heights[y][x] = data[y][x] as f32;
+        }
+    }
+
+    // decode
+    let mut offset: f32 = landscape.vertex_heights.offset;
+    for row in heights.iter_mut().take(VERTEX_CNT) {
+        for x in row.iter_mut().take(VERTEX_CNT) {
+            offset += *x;
+            *x = offset;
+        }
+        offset = row[0];
+    }
+
+    for row in &mut heights {
+        for height in row {
+            *height *= 8.0;
+        }
+    }
+
+    heights
+}
+
 fn height_map_to_pixel_heights(
     dimensions: &Dimensions,
     heights_map: HashMap<CellKey, [[f32; 65]; 65]>,
@@ -488,12 +764,162 @@ pub fn height_from_screen_space(
     heights.get(i).copied()
 }
 
+/// Write a file via a temporary file so it is never left half written
+pub fn write_atomic(path: &Path, contents: impl AsRef<[u8]>) -> std::io::Result<()> {
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+    let tmp = PathBuf::from(tmp);
+    fs::write(&tmp, contents)?;
+    fs::rename(&tmp, path).inspect_err(|_| {
+        let _ = fs::remove_file(&tmp);
+    })
+}
+
+/// Get the terrain height at an engine position, None outside of the loaded landscape
+pub fn height_at_engine(heights: &[f32], dimensions: &Dimensions, pos: Pos2) -> Option<f32> {
+    let canvas = dimensions.engine_to_canvas(pos);
+    let x = canvas.x * VERTEX_CNT as f32;
+    let y = canvas.y * VERTEX_CNT as f32;
+    if x < 0.0
+        || y < 0.0
+        || x >= dimensions.pixel_width(VERTEX_CNT) as f32
+        || y >= dimensions.pixel_height(VERTEX_CNT) as f32
+    {
+        return None;
+    }
+
+    // unset tiles are painted below the minimum
+    height_from_screen_space(heights, dimensions, x as usize, y as usize)
+        .filter(|z| *z >= dimensions.min_z)
+}
+
+/// Get the bilinearly interpolated terrain height at an engine position
+/// None outside of the loaded landscape or next to unset tiles
+pub fn interpolate_height_at_engine(
+    heights: &[f32],
+    dimensions: &Dimensions,
+    pos: Pos2,
+) -> Option<f32> {
+    let canvas = dimensions.engine_to_canvas(pos);
+    let x = canvas.x * VERTEX_CNT as f32;
+    let y = canvas.y * VERTEX_CNT as f32;
+    let max_x = dimensions.pixel_width(VERTEX_CNT) as f32 - 1.0;
+    let max_y = dimensions.pixel_height(VERTEX_CNT) as f32 - 1.0;
+    if x < 0.0 || y < 0.0 || x > max_x || y > max_y {
+        return None;
+    }
+
+    let (x0, y0) = (x.floor(), y.floor());
+    let (x1, y1) = ((x0 + 1.0).min(max_x), (y0 + 1.0).min(max_y));
+    let (tx, ty) = (x - x0, y - y0);
+    let sample = |x: f32, y: f32| {
+        height_from_screen_space(heights, dimensions, x as usize, y as usize)
+            .filter(|z| *z >= dimensions.min_z)
+    };
+    let top = sample(x0, y0)? * (1.0 - tx) + sample(x1, y0)? * tx;
+    let bottom = sample(x0, y1)? * (1.0 - tx) + sample(x1, y1)? * tx;
+    Some(top * (1.0 - ty) + bottom * ty)
+}
+
+/// Sample the terrain along a polyline every `step` units
+/// Returns (distance along the path, height) pairs
+pub fn sample_elevation_profile(
+    heights: &[f32],
+    dimensions: &Dimensions,
+    points: &[Pos2],
+    step: f32,
+) -> Vec<(f32, Option<f32>)> {
+    let mut samples = vec![];
+    let mut distance = 0.0;
+
+    for pair in points.windows(2) {
+        let (a, b) = (pair[0], pair[1]);
+        let length = a.distance(b);
+        let count = (length / step).ceil().max(1.0) as usize;
+        for i in 0..count {
+            let t = i as f32 / count as f32;
+            let pos = a.lerp(b, t);
+            samples.push((
+                distance + length * t,
+                height_at_engine(heights, dimensions, pos),
+            ));
+        }
+        distance += length;
+    }
+    if let Some(last) = points.last() {
+        samples.push((distance, height_at_engine(heights, dimensions, *last)));
+    }
+
+    samples
+}
+
+/// Get the exterior cell that contains the engine position
+pub fn cell_key_from_engine(x: f32, y: f32) -> CellKey {
+    (
+        (x / CELL_WIDTH).floor() as i32,
+        (y / CELL_WIDTH).floor() as i32,
+    )
+}
+
 fn get_rect_at_cell(dimensions: &Dimensions, to_screen: RectTransform, key: CellKey) -> Rect {
     let p00 = dimensions.cell_to_canvas(key);
     let p11 = Pos2::new(p00.x + 1.0, p00.y + 1.0);
     Rect::from_two_pos(to_screen * p00, to_screen * p11)
 }
 
+/// Distance of `p` to the line segment `a`-`b`
+pub fn distance_to_segment(p: Pos2, a: Pos2, b: Pos2) -> f32 {
+    let ab = b - a;
+    let length_sq = ab.length_sq();
+    if length_sq <= f32::EPSILON {
+        return p.distance(a);
+    }
+    let t = ((p - a).dot(ab) / length_sq).clamp(0.0, 1.0);
+    p.distance(a + t * ab)
+}
+
+/// Blend a line with the stroke width into the image
+fn rasterize_line(image: &mut DynamicImage, points: [Pos2; 2], stroke: Stroke) {
+    let Some(image) = image.as_mut_rgba8() else {
+        return;
+    };
+    let [r, g, b, a] = stroke.color.to_srgba_unmultiplied();
+    let half_width = (stroke.width * 0.5).max(0.5);
+
+    let [p1, p2] = points;
+    let min_x = (p1.x.min(p2.x) - half_width).floor().max(0.0) as u32;
+    let min_y = (p1.y.min(p2.y) - half_width).floor().max(0.0) as u32;
+    let max_x = ((p1.x.max(p2.x) + half_width).ceil().max(0.0) as u32).min(image.width());
+    let max_y = ((p1.y.max(p2.y) + half_width).ceil().max(0.0) as u32).min(image.height());
+
+    for y in min_y..max_y {
+        for x in min_x..max_x {
+            let center = Pos2::new(x as f32 + 0.5, y as f32 + 0.5);
+            // one pixel of antialiasing at the edges
+            let coverage = (half_width + 0.5 - distance_to_segment(center, p1, p2)).clamp(0.0, 1.0);
+            if coverage <= 0.0 {
+                continue;
+            }
+            let alpha = (a as f32 * coverage) as u8;
+            image
+                .get_pixel_mut(x, y)
+                .blend(&image::Rgba([r, g, b, alpha]));
+        }
+    }
+}
+
+/// A line with an arrow head at `tip`
+fn get_arrow_shapes(origin: Pos2, tip: Pos2, stroke: Stroke) -> Vec<Shape> {
+    let rot = Rot2::from_angle(std::f32::consts::TAU / 12.0);
+    let dir = (tip - origin).normalized();
+    let tip_length = (tip - origin).length().min(8.0);
+    vec![
+        Shape::line_segment([origin, tip], stroke),
+        Shape::line_segment([tip, tip - tip_length * (rot * dir)], stroke),
+        Shape::line_segment([tip, tip - tip_length * (rot.inverse() * dir)], stroke),
+    ]
+}
+
 //////////////////////////////////////////
 // TES3
 
@@ -502,3 +928,27 @@ fn get_rect_at_cell(dimensions: &Dimensions, to_screen: RectTransform, key: Cell
 pub fn get_unique_id(record: &TES3Object) -> String {
     format!("{},{}", record.tag_str(), record.editor_id())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rasterize_line_blends_unmultiplied_color() {
+        let mut image = DynamicImage::ImageRgba8(RgbaImage::new(8, 8));
+        let stroke = Stroke::new(2.0, Color32::from_rgba_unmultiplied(200, 100, 50, 255));
+        rasterize_line(
+            &mut image,
+            [Pos2::new(1.0, 4.0), Pos2::new(20.0, 4.0)],
+            stroke,
+        );
+
+        let image = image.as_rgba8().unwrap();
+        assert_eq!(image.get_pixel(4, 3).0, [200, 100, 50, 255]);
+        assert_eq!(image.get_pixel(4, 4).0, [200, 100, 50, 255]);
+        assert_eq!(image.get_pixel(4, 0).0, [0, 0, 0, 0]);
+        // antialiased around the start point
+        let alpha = image.get_pixel(0, 4).0[3];
+        assert!(alpha > 0 && alpha < 255);
+    }
+}
@@ -0,0 +1,102 @@
+use std::collections::HashMap;
+
+use egui::{emath::RectTransform, Color32, CornerRadius, Pos2, Rect, Shape, Stroke, Vec2};
+use tes3::esp::Cell;
+
+use crate::{dimensions::Dimensions, get_arrow_shapes, CellKey, CellReference};
+
+/// A door that leads into an interior
+pub fn is_interior_door(reference: &CellReference) -> bool {
+    matches!(&reference.destination, Some(d) if !d.cell.is_empty())
+}
+
+pub fn get_door_shapes(
+    to_screen: RectTransform,
+    dimensions: &Dimensions,
+    reference_records: &HashMap<CellKey, Vec<CellReference>>,
+    interior_records: &HashMap<String, Cell>,
+    zoom: f32,
+) -> Vec<Shape> {
+    let mut shapes: Vec<Shape> = Vec::new();
+    let size = (3.0 * zoom).clamp(3.0, 8.0);
+
+    for (key, references) in reference_records.iter() {
+        // check that key is within the dimensions
+        if key.0 < dimensions.min_x
+            || key.0 > dimensions.max_x
+            || key.1 < dimensions.min_y
+            || key.1 > dimensions.max_y
+        {
+            continue;
+        }
+
+        for reference in references {
+            let Some(destination) = &reference.destination else {
+                continue;
+            };
+
+            let pos = Pos2::new(reference.position[0], reference.position[1]);
+            let start = to_screen * dimensions.engine_to_canvas(pos);
+
+            if is_interior_door(reference) {
+                // broken links are red
+                let color = if interior_records.contains_key(&destination.cell.to_lowercase()) {
+                    Color32::from_rgb(80, 160, 255)
+                } else {
+                    Color32::RED
+                };
+                let rect = Rect::from_center_size(start, Vec2::splat(size));
+                shapes.push(Shape::rect_filled(rect, CornerRadius::default(), color));
+                shapes.push(Shape::rect_stroke(
+                    rect,
+                    CornerRadius::default(),
+                    Stroke::new(1.0, Color32::BLACK),
+                    egui::StrokeKind::Outside,
+                ));
+            } else {
+                let end_pos = Pos2::new(destination.translation[0], destination.translation[1]);
+                let end = to_screen * dimensions.engine_to_canvas(end_pos);
+                shapes.extend(get_arrow_shapes(
+                    start,
+                    end,
+                    Stroke::new(2.0, Color32::from_rgb(255, 140, 0)),
+                ));
+            }
+        }
+    }
+
+    shapes
+}
+
+/// Get the door closest to the screen position within `max_distance` pixels
+pub fn get_door_at<'a>(
+    to_screen: RectTransform,
+    dimensions: &Dimensions,
+    reference_records: &'a HashMap<CellKey, Vec<CellReference>>,
+    key: CellKey,
+    screen_pos: Pos2,
+    max_distance: f32,
+) -> Option<&'a CellReference> {
+    let mut found = None;
+    let mut min_dist = max_distance;
+
+    for dx in -1..=1 {
+        for dy in -1..=1 {
+            let Some(references) = reference_records.get(&(key.0 + dx, key.1 + dy)) else {
+                continue;
+            };
+
+            for reference in references.iter().filter(|r| r.destination.is_some()) {
+                let pos = Pos2::new(reference.position[0], reference.position[1]);
+                let center = to_screen * dimensions.engine_to_canvas(pos);
+                let dist = center.distance(screen_pos);
+                if dist < min_dist {
+                    min_dist = dist;
+                    found = Some(reference);
+                }
+            }
+        }
+    }
+
+    found
+}
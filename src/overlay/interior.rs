@@ -0,0 +1,90 @@
+use egui::{emath::RectTransform, emath::Rot2, Color32, Pos2, Rect, Shape, Stroke, Vec2};
+
+use crate::{overlay::references::get_color_for_reference_type, CellReference, ERefType};
+
+/// Approximate footprint in engine units, mesh bounds are not loaded
+fn get_footprint_size(reference: &CellReference) -> f32 {
+    let size = match reference.ref_type {
+        ERefType::Npc | ERefType::Creature => 64.0,
+        ERefType::Container => 48.0,
+        ERefType::Door => 96.0,
+        ERefType::Light => 16.0,
+        ERefType::Activator => 64.0,
+        ERefType::Static => 128.0,
+        ERefType::Other => 16.0,
+    };
+    size * reference.scale
+}
+
+/// Top-down position of a reference, y points north
+pub fn plan_pos(reference: &CellReference) -> Pos2 {
+    Pos2::new(reference.position[0], -reference.position[1])
+}
+
+/// Get the bounds of all references in plan space
+pub fn get_interior_bounds(references: &[CellReference]) -> Option<Rect> {
+    let mut bounds: Option<Rect> = None;
+    for reference in references {
+        let r = Rect::from_center_size(
+            plan_pos(reference),
+            Vec2::splat(get_footprint_size(reference)),
+        );
+        bounds = Some(bounds.map_or(r, |b| b.union(r)));
+    }
+    bounds
+}
+
+pub fn get_interior_shapes(
+    to_screen: RectTransform,
+    references: &[CellReference],
+    water_height: Option<f32>,
+) -> Vec<Shape> {
+    let mut shapes: Vec<Shape> = Vec::with_capacity(references.len());
+
+    // large objects first so small ones stay visible
+    let mut sorted = references.iter().collect::<Vec<_>>();
+    sorted.sort_by(|a, b| get_footprint_size(b).total_cmp(&get_footprint_size(a)));
+
+    for reference in sorted {
+        let color = get_color_for_reference_type(reference.ref_type);
+        let half = get_footprint_size(reference) / 2.0;
+        let center = plan_pos(reference);
+
+        // rotate the footprint around z
+        let rot = Rot2::from_angle(reference.rotation[2]);
+        let corners = [
+            Vec2::new(-half, -half),
+            Vec2::new(half, -half),
+            Vec2::new(half, half),
+            Vec2::new(-half, half),
+        ]
+        .map(|c| to_screen * (center + rot * c));
+
+        // references below the water level get a blue outline
+        let underwater = water_height.is_some_and(|h| reference.position[2] < h);
+        let stroke_color = if underwater {
+            Color32::from_rgb(40, 120, 255)
+        } else {
+            color
+        };
+
+        let fill = color.gamma_multiply(0.25);
+        shapes.push(Shape::convex_polygon(
+            corners.to_vec(),
+            fill,
+            Stroke::new(1.0, stroke_color),
+        ));
+
+        if reference.destination.is_some() {
+            shapes.push(Shape::circle_stroke(
+                to_screen * center,
+                5.0,
+                Stroke::new(2.0, Color32::from_rgb(255, 140, 0)),
+            ));
+        } else {
+            shapes.push(Shape::circle_filled(to_screen * center, 2.0, color));
+        }
+    }
+
+    shapes
+}
@@ -0,0 +1,259 @@
+use std::collections::{HashMap, HashSet};
+
+use egui::{
+    emath::RectTransform, epaint::TextShape, Align2, Color32, ColorImage, FontId, Pos2, Rect,
+    Shape, Vec2,
+};
+use image::{DynamicImage, Pixel};
+use tes3::esp::{Cell, Region};
+
+use crate::{dimensions::Dimensions, overlay::regions::find_region, CellKey, LabelSettings};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ELabelKind {
+    Cell,
+    Region,
+}
+
+#[derive(Debug, Clone)]
+pub struct MapLabel {
+    pub text: String,
+    pub kind: ELabelKind,
+    /// centroid in canvas coordinates
+    pub pos: Pos2,
+    /// number of cells in the group, larger groups are placed first
+    pub weight: usize,
+}
+
+/// Group the given cells into 8-connected components
+fn connected_groups(cells: &HashSet<CellKey>) -> Vec<Vec<CellKey>> {
+    let mut visited: HashSet<CellKey> = HashSet::default();
+    let mut groups = vec![];
+
+    let mut keys = cells.iter().copied().collect::<Vec<_>>();
+    keys.sort();
+
+    for start in keys {
+        if !visited.insert(start) {
+            continue;
+        }
+
+        let mut group = vec![];
+        let mut stack = vec![start];
+        while let Some(key) = stack.pop() {
+            group.push(key);
+            for dx in -1..=1 {
+                for dy in -1..=1 {
+                    let neighbour = (key.0 + dx, key.1 + dy);
+                    if cells.contains(&neighbour) && visited.insert(neighbour) {
+                        stack.push(neighbour);
+                    }
+                }
+            }
+        }
+        groups.push(group);
+    }
+
+    groups
+}
+
+fn centroid(dimensions: &Dimensions, group: &[CellKey]) -> Pos2 {
+    let sum = group.iter().fold(Vec2::ZERO, |acc, key| {
+        acc + dimensions.cell_to_canvas(*key).to_vec2() + Vec2::new(0.5, 0.5)
+    });
+    (sum / group.len() as f32).to_pos2()
+}
+
+/// Collect cell and region labels, one per connected group of cells
+pub fn get_labels(
+    dimensions: &Dimensions,
+    regn_records: &HashMap<String, Region>,
+    cell_records: &HashMap<CellKey, Cell>,
+    settings: &LabelSettings,
+) -> Vec<MapLabel> {
+    let mut cells_by_name: HashMap<String, HashSet<CellKey>> = HashMap::default();
+    let mut cells_by_region: HashMap<String, HashSet<CellKey>> = HashMap::default();
+
+    for (key, cell) in cell_records.iter() {
+        if cell.is_interior()
+            || key.0 < dimensions.min_x
+            || key.0 > dimensions.max_x
+            || key.1 < dimensions.min_y
+            || key.1 > dimensions.max_y
+        {
+            continue;
+        }
+
+        if !cell.name.is_empty() {
+            cells_by_name
+                .entry(cell.name.clone())
+                .or_default()
+                .insert(*key);
+        }
+        if let Some(region) = &cell.region {
+            cells_by_region
+                .entry(region.to_lowercase())
+                .or_default()
+                .insert(*key);
+        }
+    }
+
+    let mut labels = vec![];
+
+    if settings.show_cell_names {
+        for (name, cells) in cells_by_name.iter() {
+            for group in connected_groups(cells) {
+                labels.push(MapLabel {
+                    text: name.clone(),
+                    kind: ELabelKind::Cell,
+                    pos: centroid(dimensions, &group),
+                    weight: group.len(),
+                });
+            }
+        }
+    }
+
+    if settings.show_region_names {
+        for (id, cells) in cells_by_region.iter() {
+            // only label the largest part of a region
+            let Some(group) = connected_groups(cells).into_iter().max_by_key(|g| g.len()) else {
+                continue;
+            };
+
+            let text = find_region(regn_records, id)
+                .map(|r| r.name.clone())
+                .filter(|n| !n.is_empty())
+                .unwrap_or(id.clone());
+
+            labels.push(MapLabel {
+                text,
+                kind: ELabelKind::Region,
+                pos: centroid(dimensions, &group),
+                weight: group.len(),
+            });
+        }
+    }
+
+    // cells first, then by size
+    labels.sort_by(|a, b| {
+        a.kind
+            .cmp(&b.kind)
+            .then(b.weight.cmp(&a.weight))
+            .then(a.text.cmp(&b.text))
+    });
+
+    labels
+}
+
+/// Lay out the labels and drop the ones that would overlap an already placed label
+pub fn get_label_shapes(
+    fonts: &egui::epaint::text::Fonts,
+    to_screen: RectTransform,
+    labels: &[MapLabel],
+    settings: &LabelSettings,
+    zoom: f32,
+) -> Vec<Shape> {
+    let mut shapes: Vec<Shape> = Vec::with_capacity(labels.len());
+    let mut placed: Vec<Rect> = vec![];
+
+    for label in labels {
+        let (size, color) = match label.kind {
+            ELabelKind::Cell => (settings.font_size, Color32::WHITE),
+            ELabelKind::Region => (settings.font_size * 1.25, Color32::from_rgb(230, 220, 190)),
+        };
+        let size = if settings.scale_with_zoom {
+            size * zoom
+        } else {
+            size
+        };
+        if size < 4.0 {
+            continue;
+        }
+
+        let font_id = FontId::proportional(size);
+        let center = to_screen * label.pos;
+        let galley = fonts.layout_no_wrap(label.text.clone(), font_id.clone(), color);
+        let rect = Align2::CENTER_CENTER.anchor_size(center, galley.size());
+
+        // collision avoidance
+        if placed.iter().any(|r| r.expand(2.0).intersects(rect)) {
+            continue;
+        }
+        placed.push(rect);
+
+        // halo
+        if settings.halo {
+            let halo_galley = fonts.layout_no_wrap(label.text.clone(), font_id, Color32::BLACK);
+            for offset in [
+                Vec2::new(-1.0, -1.0),
+                Vec2::new(0.0, -1.0),
+                Vec2::new(1.0, -1.0),
+                Vec2::new(-1.0, 0.0),
+                Vec2::new(1.0, 0.0),
+                Vec2::new(-1.0, 1.0),
+                Vec2::new(0.0, 1.0),
+                Vec2::new(1.0, 1.0),
+            ] {
+                shapes.push(Shape::galley(
+                    rect.min + offset,
+                    halo_galley.clone(),
+                    Color32::BLACK,
+                ));
+            }
+        }
+
+        shapes.push(Shape::galley(rect.min, galley, color));
+    }
+
+    shapes
+}
+
+/// Draw a text shape into an image by copying the glyphs from the font atlas
+pub fn rasterize_text(image: &mut DynamicImage, shape: &TextShape, font_atlas: &ColorImage) {
+    let Some(image) = image.as_mut_rgba8() else {
+        return;
+    };
+    let color = shape.override_text_color.unwrap_or(shape.fallback_color);
+
+    for row in shape.galley.rows.iter() {
+        for glyph in row.glyphs.iter() {
+            let uv = glyph.uv_rect;
+            if uv.is_nothing() {
+                continue;
+            }
+
+            let min = shape.pos + row.pos.to_vec2() + glyph.pos.to_vec2() + uv.offset;
+            let width = uv.size.x.round() as i64;
+            let height = uv.size.y.round() as i64;
+            let uv_width = (uv.max[0] - uv.min[0]) as f32;
+            let uv_height = (uv.max[1] - uv.min[1]) as f32;
+
+            for y in 0..height {
+                for x in 0..width {
+                    let tx = min.x as i64 + x;
+                    let ty = min.y as i64 + y;
+                    if tx < 0 || ty < 0 || tx >= image.width() as i64 || ty >= image.height() as i64
+                    {
+                        continue;
+                    }
+
+                    // nearest neighbour lookup in the atlas
+                    let u = uv.min[0] as usize + (x as f32 / width as f32 * uv_width) as usize;
+                    let v = uv.min[1] as usize + (y as f32 / height as f32 * uv_height) as usize;
+                    if u >= font_atlas.width() || v >= font_atlas.height() {
+                        continue;
+                    }
+                    let coverage = font_atlas[(u, v)].a() as f32 / 255.0;
+                    if coverage <= 0.0 {
+                        continue;
+                    }
+
+                    let [r, g, b, a] = color.to_srgba_unmultiplied();
+                    let alpha = (a as f32 * coverage) as u8;
+                    let pixel = image.get_pixel_mut(tx as u32, ty as u32);
+                    pixel.blend(&image::Rgba([r, g, b, alpha]));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,42 @@
+use egui::{emath::RectTransform, Color32, Pos2, Shape, Stroke};
+
+use crate::dimensions::Dimensions;
+
+pub fn get_measure_shapes(
+    to_screen: RectTransform,
+    dimensions: &Dimensions,
+    points: &[Pos2],
+    closed: bool,
+    hover_pos: Option<Pos2>,
+) -> Vec<Shape> {
+    let mut shapes: Vec<Shape> = Vec::new();
+    let stroke = Stroke::new(2.0, Color32::from_rgb(0, 220, 255));
+
+    let mut screen_points = points
+        .iter()
+        .map(|p| to_screen * dimensions.engine_to_canvas(*p))
+        .collect::<Vec<_>>();
+
+    // close the polygon
+    if closed && screen_points.len() > 2 {
+        screen_points.push(screen_points[0]);
+    }
+
+    shapes.push(Shape::line(screen_points.clone(), stroke));
+
+    // preview the next point
+    if let (Some(last), Some(hover_pos)) = (screen_points.last(), hover_pos) {
+        shapes.extend(Shape::dashed_line(
+            &[*last, hover_pos],
+            Stroke::new(1.0, stroke.color),
+            6.0,
+            4.0,
+        ));
+    }
+
+    for point in screen_points.iter() {
+        shapes.push(Shape::circle_filled(*point, 3.0, stroke.color));
+    }
+
+    shapes
+}
@@ -1,7 +1,13 @@
 pub mod cities;
 pub mod conflicts;
+pub mod doors;
 pub mod grid;
+pub mod interior;
+pub mod labels;
+pub mod measure;
 pub mod mod_splines;
 pub mod paths;
+pub mod references;
 pub mod regions;
 pub mod travel;
+pub mod validation;
@@ -7,12 +7,11 @@ pub fn get_segments_shapes(
     dimensions: &Dimensions,
     zoom: f32,
     editor_data: &EditorData,
-    hover_pos: &Option<Pos2>,
 ) -> Vec<Shape> {
     let mut shapes: Vec<Shape> = Vec::new();
 
     // go through all segments
-    for (_e, segment) in editor_data.segments.iter().filter(|(_, s)| s.selected) {
+    for (id, segment) in editor_data.segments.iter().filter(|(_, s)| s.selected) {
         // is current segment?
         let is_current = if let Some(current_segment) = &editor_data.current_segment {
             current_segment == &segment.id
@@ -37,22 +36,29 @@ pub fn get_segments_shapes(
             }
         }
 
-        for point in points.iter() {
+        for (i, point) in points.iter().enumerate() {
             let pos2 = Pos2::new(point[0], point[1]);
             let canvas_pos = dimensions.engine_to_canvas(pos2);
             let center = to_screen * canvas_pos;
+            let point_ref = (id.clone(), i);
             let mut radius = 2.0;
-            if let Some(hover_pos) = hover_pos {
-                if (center - *hover_pos).length() < 10.0 {
-                    radius = 3.0;
-                }
+            // same point as picked by a click
+            if editor_data.hovered_point.as_ref() == Some(&point_ref) {
+                radius = 3.0;
             }
             let dot = Shape::circle_filled(center, radius * zoom, point_color);
             shapes.push(dot);
+            if editor_data.selected_points.contains(&point_ref) {
+                shapes.push(Shape::circle_stroke(
+                    center,
+                    radius * zoom + 2.0,
+                    egui::Stroke::new(1.5, Color32::ORANGE),
+                ));
+            }
         }
 
         // connect the points with lines
-        for i in 0..points.len() - 1 {
+        for i in 0..points.len().saturating_sub(1) {
             let p0 = Pos2::new(points[i][0], points[i][1]);
             let p1 = Pos2::new(points[i + 1][0], points[i + 1][1]);
 
@@ -67,6 +73,72 @@ pub fn get_segments_shapes(
         }
     }
 
+    // spline tool preview
+    if let Some((id, points)) = &editor_data.spline_preview {
+        if editor_data.segments.get(id).is_some_and(|s| s.selected) {
+            let screen_points = points
+                .iter()
+                .map(|p| to_screen * dimensions.engine_to_canvas(Pos2::new(p.x, p.y)))
+                .collect::<Vec<_>>();
+            shapes.extend(Shape::dashed_line(
+                &screen_points,
+                egui::Stroke::new(2.0, Color32::from_rgb(0, 220, 255)),
+                8.0,
+                4.0,
+            ));
+            for point in screen_points {
+                shapes.push(Shape::circle_filled(
+                    point,
+                    1.5 * zoom.max(1.0),
+                    Color32::from_rgb(0, 220, 255),
+                ));
+            }
+        }
+    }
+
+    // picked point of the current segment
+    if let Some((id, i)) = &editor_data.picked_point {
+        if let Some(point) = editor_data
+            .segments
+            .get(id)
+            .and_then(|s| s.route1.as_ref())
+            .and_then(|r| r.get(*i))
+        {
+            let center = to_screen * dimensions.engine_to_canvas(Pos2::new(point.x, point.y));
+            shapes.push(Shape::circle_stroke(
+                center,
+                4.0 * zoom.max(1.0),
+                egui::Stroke::new(2.0, Color32::WHITE),
+            ));
+        }
+    }
+
+    // water pathfinder endpoints
+    for endpoint in editor_data.path_endpoints.iter().flatten() {
+        let center = to_screen * dimensions.engine_to_canvas(*endpoint);
+        shapes.push(Shape::circle_stroke(
+            center,
+            5.0,
+            egui::Stroke::new(2.0, Color32::from_rgb(0, 220, 255)),
+        ));
+    }
+
+    // selection box
+    if let Some((start, end)) = editor_data.box_select {
+        let rect = egui::Rect::from_two_pos(to_screen * start, to_screen * end);
+        shapes.push(Shape::rect_filled(
+            rect,
+            0.0,
+            Color32::from_rgba_unmultiplied(255, 165, 0, 30),
+        ));
+        shapes.push(Shape::rect_stroke(
+            rect,
+            0.0,
+            egui::Stroke::new(1.0, Color32::ORANGE),
+            egui::StrokeKind::Middle,
+        ));
+    }
+
     // ports
     for (_name, port) in editor_data.ports.iter() {
         for data in port.data.values() {
@@ -76,6 +148,18 @@ pub fn get_segments_shapes(
                 let center = to_screen * dimensions.engine_to_canvas(pos2);
                 let dot = Shape::circle_filled(center, 2.0 * zoom, Color32::BLUE);
                 shapes.push(dot);
+
+                // heading with a handle to turn it
+                let tip = to_screen * dimensions.engine_to_canvas(data.heading_tip());
+                shapes.push(Shape::line_segment(
+                    [center, tip],
+                    egui::Stroke::new(1.5, Color32::BLUE),
+                ));
+                shapes.push(Shape::circle_stroke(
+                    tip,
+                    4.0,
+                    egui::Stroke::new(1.5, Color32::BLUE),
+                ));
             }
 
             // reverse start positions
@@ -92,3 +176,80 @@ pub fn get_segments_shapes(
 
     shapes
 }
+
+/// Mark the positions of route validation errors
+pub fn get_route_error_shapes(
+    to_screen: RectTransform,
+    dimensions: &Dimensions,
+    editor_data: &EditorData,
+) -> Vec<Shape> {
+    let mut shapes: Vec<Shape> = Vec::new();
+
+    for metadata in editor_data.routes_metadata.values() {
+        for error in &metadata.errors {
+            if let Some(position) = error.position {
+                let center = to_screen * dimensions.engine_to_canvas(position);
+                shapes.push(Shape::circle_stroke(
+                    center,
+                    8.0,
+                    egui::Stroke::new(2.0, Color32::RED),
+                ));
+            }
+        }
+    }
+
+    shapes
+}
+
+/// Simulated vehicle of the route playback with its track and sharp turns
+pub fn get_playback_shapes(
+    to_screen: RectTransform,
+    dimensions: &Dimensions,
+    editor_data: &EditorData,
+) -> Vec<Shape> {
+    let mut shapes: Vec<Shape> = Vec::new();
+    let Some(playback) = &editor_data.playback else {
+        return shapes;
+    };
+    let to_screen_pos = |p: Pos2| to_screen * dimensions.engine_to_canvas(p);
+
+    // the simulated track
+    let track = playback
+        .track
+        .iter()
+        .map(|s| to_screen_pos(s.position))
+        .collect::<Vec<_>>();
+    shapes.push(Shape::line(
+        track,
+        egui::Stroke::new(1.0, Color32::from_white_alpha(160)),
+    ));
+
+    for position in &playback.sharp_turns {
+        shapes.push(Shape::circle_stroke(
+            to_screen_pos(*position),
+            6.0,
+            egui::Stroke::new(2.0, Color32::ORANGE),
+        ));
+    }
+
+    // vehicle marker pointing in the heading
+    if let Some(sample) = playback.current() {
+        let center = to_screen_pos(sample.position);
+        let ahead = to_screen_pos(
+            sample.position + egui::vec2(sample.heading.sin(), sample.heading.cos()) * 64.0,
+        );
+        let dir = (ahead - center).normalized();
+        let side = egui::vec2(-dir.y, dir.x);
+        shapes.push(Shape::convex_polygon(
+            vec![
+                center + dir * 10.0,
+                center - dir * 6.0 + side * 6.0,
+                center - dir * 6.0 - side * 6.0,
+            ],
+            Color32::from_rgb(255, 220, 0),
+            egui::Stroke::new(1.0, Color32::BLACK),
+        ));
+    }
+
+    shapes
+}
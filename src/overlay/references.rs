@@ -0,0 +1,101 @@
+use std::collections::HashMap;
+
+use egui::{emath::RectTransform, Color32, Pos2, Rect, Shape};
+
+use crate::{dimensions::Dimensions, CellKey, CellReference, ERefType};
+
+pub fn get_color_for_reference_type(ref_type: ERefType) -> Color32 {
+    match ref_type {
+        ERefType::Npc => Color32::from_rgb(255, 215, 0),
+        ERefType::Creature => Color32::from_rgb(220, 60, 60),
+        ERefType::Container => Color32::from_rgb(160, 100, 40),
+        ERefType::Door => Color32::from_rgb(80, 160, 255),
+        ERefType::Light => Color32::from_rgb(255, 255, 160),
+        ERefType::Activator => Color32::from_rgb(200, 80, 220),
+        ERefType::Static => Color32::from_rgb(150, 150, 150),
+        ERefType::Other => Color32::from_rgb(90, 200, 90),
+    }
+}
+
+pub fn is_reference_type_visible(filters: &HashMap<ERefType, bool>, ref_type: ERefType) -> bool {
+    filters.get(&ref_type).copied().unwrap_or(true)
+}
+
+pub fn get_reference_shapes(
+    to_screen: RectTransform,
+    dimensions: &Dimensions,
+    reference_records: &HashMap<CellKey, Vec<CellReference>>,
+    filters: &HashMap<ERefType, bool>,
+    clip_rect: Rect,
+    zoom: f32,
+) -> Vec<Shape> {
+    let mut shapes: Vec<Shape> = Vec::new();
+    let radius = zoom.clamp(1.0, 4.0);
+
+    for (key, references) in reference_records.iter() {
+        // check that key is within the dimensions
+        if key.0 < dimensions.min_x
+            || key.0 > dimensions.max_x
+            || key.1 < dimensions.min_y
+            || key.1 > dimensions.max_y
+        {
+            continue;
+        }
+
+        for reference in references {
+            if !is_reference_type_visible(filters, reference.ref_type) {
+                continue;
+            }
+
+            let pos = Pos2::new(reference.position[0], reference.position[1]);
+            let center = to_screen * dimensions.engine_to_canvas(pos);
+            if !clip_rect.contains(center) {
+                continue;
+            }
+
+            let color = get_color_for_reference_type(reference.ref_type);
+            shapes.push(Shape::circle_filled(center, radius, color));
+        }
+    }
+
+    shapes
+}
+
+/// Get the visible reference closest to the screen position within `max_distance` pixels
+pub fn get_reference_at<'a>(
+    to_screen: RectTransform,
+    dimensions: &Dimensions,
+    reference_records: &'a HashMap<CellKey, Vec<CellReference>>,
+    filters: &HashMap<ERefType, bool>,
+    key: CellKey,
+    screen_pos: Pos2,
+    max_distance: f32,
+) -> Option<&'a CellReference> {
+    let mut found = None;
+    let mut min_dist = max_distance;
+
+    // references can be placed slightly outside their cell
+    for dx in -1..=1 {
+        for dy in -1..=1 {
+            let Some(references) = reference_records.get(&(key.0 + dx, key.1 + dy)) else {
+                continue;
+            };
+
+            for reference in references {
+                if !is_reference_type_visible(filters, reference.ref_type) {
+                    continue;
+                }
+
+                let pos = Pos2::new(reference.position[0], reference.position[1]);
+                let center = to_screen * dimensions.engine_to_canvas(pos);
+                let dist = center.distance(screen_pos);
+                if dist < min_dist {
+                    min_dist = dist;
+                    found = Some(reference);
+                }
+            }
+        }
+    }
+
+    found
+}
@@ -1,38 +1,98 @@
 use std::collections::HashMap;
 
-use egui::{emath::RectTransform, Color32, CornerRadius, Shape};
+use egui::{emath::RectTransform, Color32, CornerRadius, Shape, Stroke};
 use tes3::esp::{Cell, Region};
 
-use crate::{dimensions::Dimensions, get_rect_at_cell, CellKey};
+use crate::{dimensions::Dimensions, get_rect_at_cell, CellKey, RegionSettings};
+
+/// Look up a region by id, cells don't always use the same case as the REGN record
+pub fn find_region<'a>(regn_records: &'a HashMap<String, Region>, id: &str) -> Option<&'a Region> {
+    regn_records.get(id).or_else(|| {
+        regn_records
+            .values()
+            .find(|r| r.id.eq_ignore_ascii_case(id))
+    })
+}
+
+fn get_region_id(cell_records: &HashMap<CellKey, Cell>, key: CellKey) -> Option<String> {
+    cell_records
+        .get(&key)
+        .and_then(|c| c.region.as_ref())
+        .map(|r| r.to_lowercase())
+}
 
 pub fn get_region_shapes(
     to_screen: RectTransform,
     dimensions: &Dimensions,
     regn_records: &HashMap<String, Region>,
     cell_records: &HashMap<CellKey, Cell>,
+    settings: &RegionSettings,
 ) -> Vec<Shape> {
     let shapes_len =
         (dimensions.max_x - dimensions.min_x + 1) * (dimensions.max_y - dimensions.min_y + 1);
     let mut shapes: Vec<Shape> = Vec::with_capacity(shapes_len as usize);
+    let alpha = (settings.opacity.clamp(0.0, 1.0) * 255.0) as u8;
 
     for cy in dimensions.min_y..dimensions.max_y + 1 {
         for cx in dimensions.min_x..dimensions.max_x + 1 {
             // get region
             let key = (cx, cy);
-            if let Some(cell) = cell_records.get(&key) {
-                if let Some(region_name) = &cell.region {
-                    if let Some(region) = regn_records.get(region_name) {
-                        let region_color = Color32::from_rgb(
-                            region.map_color[0],
-                            region.map_color[1],
-                            region.map_color[2],
-                        );
-
-                        let rect = get_rect_at_cell(dimensions, to_screen, key);
-                        let shape = Shape::rect_filled(rect, CornerRadius::default(), region_color);
-                        shapes.push(shape);
-                    }
+            let Some(cell) = cell_records.get(&key) else {
+                continue;
+            };
+            let Some(region_name) = &cell.region else {
+                continue;
+            };
+            let Some(region) = find_region(regn_records, region_name) else {
+                continue;
+            };
+
+            let rect = get_rect_at_cell(dimensions, to_screen, key);
+
+            if settings.outlines_only {
+                // only draw the edges to cells of another region
+                let color = Color32::from_rgb(
+                    region.map_color[0],
+                    region.map_color[1],
+                    region.map_color[2],
+                );
+                let stroke = Stroke::new(2.0, color);
+                let id = Some(region_name.to_lowercase());
+
+                // cell y grows north, screen y grows south
+                if get_region_id(cell_records, (cx, cy + 1)) != id {
+                    shapes.push(Shape::line_segment(
+                        [rect.left_top(), rect.right_top()],
+                        stroke,
+                    ));
+                }
+                if get_region_id(cell_records, (cx, cy - 1)) != id {
+                    shapes.push(Shape::line_segment(
+                        [rect.left_bottom(), rect.right_bottom()],
+                        stroke,
+                    ));
+                }
+                if get_region_id(cell_records, (cx - 1, cy)) != id {
+                    shapes.push(Shape::line_segment(
+                        [rect.left_top(), rect.left_bottom()],
+                        stroke,
+                    ));
+                }
+                if get_region_id(cell_records, (cx + 1, cy)) != id {
+                    shapes.push(Shape::line_segment(
+                        [rect.right_top(), rect.right_bottom()],
+                        stroke,
+                    ));
                 }
+            } else {
+                let region_color = Color32::from_rgba_unmultiplied(
+                    region.map_color[0],
+                    region.map_color[1],
+                    region.map_color[2],
+                    alpha,
+                );
+                let shape = Shape::rect_filled(rect, CornerRadius::default(), region_color);
+                shapes.push(shape);
             }
         }
     }
@@ -2,26 +2,95 @@ use std::collections::HashMap;
 
 use eframe::emath::RectTransform;
 use eframe::epaint::{Color32, Shape, Stroke};
-use egui::Vec2;
+use egui::{ecolor::Hsva, Pos2};
 
 use crate::dimensions::Dimensions;
-use crate::CellKey;
+use crate::{distance_to_segment, TravelEdge};
 
-fn get_color_for_class(class: &str) -> Color32 {
+/// Default colors of the vanilla and Tamriel Rebuilt services
+fn get_default_color_for_class(class: &str) -> Option<Color32> {
     match class {
-        "Shipmaster" => Color32::BLUE,
-        "Caravaner" => Color32::GOLD,
-        "Gondolier" => Color32::GRAY,
-        "T_Mw_RiverstriderService" => Color32::LIGHT_BLUE,
-        _ => Color32::RED,
+        "Shipmaster" => Some(Color32::BLUE),
+        "Caravaner" => Some(Color32::GOLD),
+        "Gondolier" => Some(Color32::GRAY),
+        "T_Mw_RiverstriderService" => Some(Color32::LIGHT_BLUE),
+        _ => None,
     }
 }
 
+/// Assign a color to every travel class, user overrides take precedence
+pub fn get_travel_class_colors(
+    classes: &[&String],
+    overrides: &HashMap<String, Color32>,
+) -> HashMap<String, Color32> {
+    let mut sorted = classes.to_vec();
+    sorted.sort();
+
+    // evenly spaced hues for all classes without a default color
+    let unknown = sorted
+        .iter()
+        .filter(|c| get_default_color_for_class(c).is_none())
+        .count()
+        .max(1);
+
+    let mut colors = HashMap::default();
+    let mut i = 0;
+    for class in sorted {
+        let color = if let Some(color) = overrides.get(class) {
+            *color
+        } else if let Some(color) = get_default_color_for_class(class) {
+            color
+        } else {
+            let hue = i as f32 / unknown as f32;
+            i += 1;
+            Hsva::new(hue, 0.85, 0.95, 1.0).into()
+        };
+        colors.insert(class.clone(), color);
+    }
+    colors
+}
+
+/// Human readable name of a class id, e.g. T_Mw_RiverstriderService -> Riverstrider Service
+pub fn get_friendly_class_name(class: &str, class_names: &HashMap<String, String>) -> String {
+    if let Some(name) = class_names.get(&class.to_lowercase()) {
+        if !name.is_empty() {
+            return name.clone();
+        }
+    }
+
+    // strip mod prefixes like T_Mw_
+    let name = class.rsplit('_').next().unwrap_or(class);
+    let mut result = String::new();
+    for (i, c) in name.chars().enumerate() {
+        if i > 0 && c.is_uppercase() {
+            result.push(' ');
+        }
+        result.push(c);
+    }
+    result
+}
+
+/// Screen space endpoints of a travel edge
+fn get_edge_points(
+    to_screen: RectTransform,
+    dimensions: &Dimensions,
+    edge: &TravelEdge,
+) -> [Pos2; 2] {
+    let start = Pos2::new(edge.start[0], edge.start[1]);
+    let end = Pos2::new(edge.destination[0], edge.destination[1]);
+    [
+        to_screen * dimensions.engine_to_canvas(start),
+        to_screen * dimensions.engine_to_canvas(end),
+    ]
+}
+
 pub fn get_travel_shapes(
     to_screen: RectTransform,
     dimensions: &Dimensions,
-    edges: &HashMap<String, Vec<(CellKey, CellKey)>>,
+    edges: &HashMap<String, Vec<TravelEdge>>,
     class_name: &str,
+    color: Color32,
+    selected_edge: Option<&TravelEdge>,
 ) -> Vec<Shape> {
     let shapes_len = edges
         .iter()
@@ -34,20 +103,51 @@ pub fn get_travel_shapes(
             continue;
         }
 
-        // get class color
-        let color = get_color_for_class(class);
-
-        for (key, value) in destinations {
-            let p00 = dimensions.cell_to_canvas(*key) + Vec2::new(0.5, 0.5);
-            let p11 = dimensions.cell_to_canvas(*value) + Vec2::new(0.5, 0.5);
-
-            let line = Shape::LineSegment {
-                points: [to_screen * p00, to_screen * p11],
-                stroke: Stroke::new(2.0, color),
+        for edge in destinations {
+            let points = get_edge_points(to_screen, dimensions, edge);
+            let width = if selected_edge == Some(edge) {
+                4.0
+            } else {
+                2.0
             };
-            shapes.push(line);
+
+            shapes.push(Shape::LineSegment {
+                points,
+                stroke: Stroke::new(width, color),
+            });
+            shapes.push(Shape::circle_filled(points[0], width, color));
         }
     }
 
     shapes
 }
+
+/// Get the visible travel edge closest to the screen position within `max_distance` pixels
+pub fn get_travel_edge_at<'a>(
+    to_screen: RectTransform,
+    dimensions: &Dimensions,
+    edges: &'a HashMap<String, Vec<TravelEdge>>,
+    overlay_travel: &HashMap<String, bool>,
+    screen_pos: Pos2,
+    max_distance: f32,
+) -> Option<&'a TravelEdge> {
+    let mut found = None;
+    let mut min_dist = max_distance;
+
+    for (class, class_edges) in edges.iter() {
+        if !overlay_travel.get(class).copied().unwrap_or_default() {
+            continue;
+        }
+
+        for edge in class_edges {
+            let [a, b] = get_edge_points(to_screen, dimensions, edge);
+            let dist = distance_to_segment(screen_pos, a, b);
+            if dist < min_dist {
+                min_dist = dist;
+                found = Some(edge);
+            }
+        }
+    }
+
+    found
+}
@@ -0,0 +1,41 @@
+use egui::{emath::RectTransform, CornerRadius, Shape, Stroke, StrokeKind};
+
+use crate::dimensions::Dimensions;
+use crate::get_rect_at_cell;
+use crate::validation::CellIssue;
+
+pub fn get_validation_shapes(
+    to_screen: RectTransform,
+    dimensions: &Dimensions,
+    cell_issues: &[CellIssue],
+) -> Vec<Shape> {
+    let mut shapes: Vec<Shape> = Vec::with_capacity(cell_issues.len());
+
+    for issue in cell_issues {
+        let key = issue.key;
+        // check that key is within the dimensions
+        if key.0 < dimensions.min_x
+            || key.0 > dimensions.max_x
+            || key.1 < dimensions.min_y
+            || key.1 > dimensions.max_y
+        {
+            continue;
+        }
+
+        let color = issue.issue.color();
+        let rect = get_rect_at_cell(dimensions, to_screen, key);
+        shapes.push(Shape::rect_filled(
+            rect,
+            CornerRadius::default(),
+            color.gamma_multiply(0.3),
+        ));
+        shapes.push(Shape::rect_stroke(
+            rect.shrink(1.0),
+            CornerRadius::default(),
+            Stroke::new(1.0, color),
+            StrokeKind::Inside,
+        ));
+    }
+
+    shapes
+}
@@ -0,0 +1,395 @@
+use std::{
+    cmp::Ordering,
+    collections::{BinaryHeap, HashMap},
+};
+
+use egui::Pos2;
+use serde::{Deserialize, Serialize};
+
+use crate::{dimensions::Dimensions, CELL_WIDTH, VERTEX_CNT};
+
+/// Distance between two height buffer pixels in units
+const VERTEX_SPACING: f32 = CELL_WIDTH / VERTEX_CNT as f32;
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct WaterPathSettings {
+    /// minimum water depth in units
+    pub min_depth: f32,
+    /// minimum distance to the shore in units
+    pub clearance: f32,
+    /// height vertices per grid node, larger is faster but coarser
+    pub resolution: usize,
+    /// cells around the start and goal that are searched
+    pub margin: i32,
+}
+
+impl Default for WaterPathSettings {
+    fn default() -> Self {
+        Self {
+            min_depth: 128.0,
+            clearance: 256.0,
+            resolution: 4,
+            margin: 8,
+        }
+    }
+}
+
+/// Grid of passable nodes over the height buffer
+struct WaterGrid {
+    width: usize,
+    height: usize,
+    /// node 0,0 in height buffer pixels
+    origin: (usize, usize),
+    step: usize,
+    blocked: Vec<bool>,
+}
+
+impl WaterGrid {
+    fn new(
+        heights: &[f32],
+        dimensions: &Dimensions,
+        bounds: (usize, usize, usize, usize),
+        settings: &WaterPathSettings,
+    ) -> Self {
+        let stride = dimensions.stride(VERTEX_CNT);
+        let step = settings.resolution.max(1);
+        let (min_x, min_y, max_x, max_y) = bounds;
+        let width = (max_x - min_x) / step + 1;
+        let height = (max_y - min_y) / step + 1;
+
+        // land and shallow water
+        let mut land = vec![false; width * height];
+        for gy in 0..height {
+            for gx in 0..width {
+                let i = (min_y + gy * step) * stride + min_x + gx * step;
+                let z = heights.get(i).copied().unwrap_or(dimensions.min_z - 1.0);
+                // tiles without landscape are open sea
+                let unset = z < dimensions.min_z;
+                land[gy * width + gx] = !unset && z > -settings.min_depth;
+            }
+        }
+
+        // keep the clearance to the shore
+        let radius = (settings.clearance / (VERTEX_SPACING * step as f32)).ceil() as isize;
+        let mut blocked = land.clone();
+        if radius > 0 {
+            for gy in 0..height as isize {
+                for gx in 0..width as isize {
+                    if !land[gy as usize * width + gx as usize] {
+                        continue;
+                    }
+                    for dy in -radius..=radius {
+                        for dx in -radius..=radius {
+                            let (x, y) = (gx + dx, gy + dy);
+                            if x < 0 || y < 0 || x >= width as isize || y >= height as isize {
+                                continue;
+                            }
+                            if dx * dx + dy * dy <= radius * radius {
+                                blocked[y as usize * width + x as usize] = true;
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        Self {
+            width,
+            height,
+            origin: (min_x, min_y),
+            step,
+            blocked,
+        }
+    }
+
+    fn is_free(&self, x: isize, y: isize) -> bool {
+        x >= 0
+            && y >= 0
+            && (x as usize) < self.width
+            && (y as usize) < self.height
+            && !self.blocked[y as usize * self.width + x as usize]
+    }
+
+    fn node_to_canvas(&self, node: (isize, isize)) -> Pos2 {
+        Pos2::new(
+            (self.origin.0 as f32 + (node.0 as usize * self.step) as f32) / VERTEX_CNT as f32,
+            (self.origin.1 as f32 + (node.1 as usize * self.step) as f32) / VERTEX_CNT as f32,
+        )
+    }
+
+    fn canvas_to_node(&self, pos: Pos2) -> (isize, isize) {
+        (
+            ((pos.x * VERTEX_CNT as f32 - self.origin.0 as f32) / self.step as f32).round()
+                as isize,
+            ((pos.y * VERTEX_CNT as f32 - self.origin.1 as f32) / self.step as f32).round()
+                as isize,
+        )
+    }
+
+    /// Nearest free node, ports are usually right at the shore
+    fn nearest_free(&self, node: (isize, isize), max_radius: isize) -> Option<(isize, isize)> {
+        for r in 0..=max_radius {
+            let mut best = None;
+            let mut best_dist = isize::MAX;
+            for dy in -r..=r {
+                for dx in -r..=r {
+                    if dx.abs() != r && dy.abs() != r {
+                        continue;
+                    }
+                    let n = (node.0 + dx, node.1 + dy);
+                    if self.is_free(n.0, n.1) && dx * dx + dy * dy < best_dist {
+                        best_dist = dx * dx + dy * dy;
+                        best = Some(n);
+                    }
+                }
+            }
+            if best.is_some() {
+                return best;
+            }
+        }
+        None
+    }
+
+    /// All nodes on the line are free
+    fn line_of_sight(&self, a: (isize, isize), b: (isize, isize)) -> bool {
+        let (mut x, mut y) = a;
+        let dx = (b.0 - a.0).abs();
+        let dy = -(b.1 - a.1).abs();
+        let sx = if a.0 < b.0 { 1 } else { -1 };
+        let sy = if a.1 < b.1 { 1 } else { -1 };
+        let mut err = dx + dy;
+        loop {
+            if !self.is_free(x, y) {
+                return false;
+            }
+            if (x, y) == b {
+                return true;
+            }
+            let e2 = 2 * err;
+            if e2 >= dy {
+                err += dy;
+                x += sx;
+            }
+            if e2 <= dx {
+                err += dx;
+                y += sy;
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct OpenNode {
+    cost: f32,
+    node: (isize, isize),
+}
+
+impl Eq for OpenNode {}
+
+impl Ord for OpenNode {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // min heap
+        other.cost.total_cmp(&self.cost)
+    }
+}
+
+impl PartialOrd for OpenNode {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+fn node_distance(a: (isize, isize), b: (isize, isize)) -> f32 {
+    (((a.0 - b.0).pow(2) + (a.1 - b.1).pow(2)) as f32).sqrt()
+}
+
+/// Theta* search for an any-angle path over water between two engine positions
+pub fn find_water_path(
+    heights: &[f32],
+    dimensions: &Dimensions,
+    start: Pos2,
+    goal: Pos2,
+    settings: &WaterPathSettings,
+) -> Result<Vec<Pos2>, String> {
+    if heights.is_empty() {
+        return Err("No height data loaded".to_owned());
+    }
+
+    // search area around start and goal
+    let start_canvas = dimensions.engine_to_canvas(start);
+    let goal_canvas = dimensions.engine_to_canvas(goal);
+    let margin = settings.margin.max(0) as f32;
+    let max_x = dimensions.pixel_width(VERTEX_CNT).saturating_sub(1) as f32;
+    let max_y = dimensions.pixel_height(VERTEX_CNT).saturating_sub(1) as f32;
+    let clamp = |v: f32, max: f32| (v * VERTEX_CNT as f32).clamp(0.0, max) as usize;
+    let bounds = (
+        clamp(start_canvas.x.min(goal_canvas.x) - margin, max_x),
+        clamp(start_canvas.y.min(goal_canvas.y) - margin, max_y),
+        clamp(start_canvas.x.max(goal_canvas.x) + margin, max_x),
+        clamp(start_canvas.y.max(goal_canvas.y) + margin, max_y),
+    );
+
+    let grid = WaterGrid::new(heights, dimensions, bounds, settings);
+    let snap_radius = (grid.width.max(grid.height) / 8).max(4) as isize;
+    let start_node = grid
+        .nearest_free(grid.canvas_to_node(start_canvas), snap_radius)
+        .ok_or("No water near the start")?;
+    let goal_node = grid
+        .nearest_free(grid.canvas_to_node(goal_canvas), snap_radius)
+        .ok_or("No water near the goal")?;
+
+    let mut open = BinaryHeap::new();
+    let mut g: HashMap<(isize, isize), f32> = HashMap::default();
+    let mut parent: HashMap<(isize, isize), (isize, isize)> = HashMap::default();
+    g.insert(start_node, 0.0);
+    parent.insert(start_node, start_node);
+    open.push(OpenNode {
+        cost: node_distance(start_node, goal_node),
+        node: start_node,
+    });
+
+    let mut found = false;
+    while let Some(OpenNode { node, cost }) = open.pop() {
+        if node == goal_node {
+            found = true;
+            break;
+        }
+        let node_g = g[&node];
+        // skip outdated entries
+        if cost > node_g + node_distance(node, goal_node) + 1e-3 {
+            continue;
+        }
+
+        for dy in -1..=1 {
+            for dx in -1..=1 {
+                if dx == 0 && dy == 0 {
+                    continue;
+                }
+                let next = (node.0 + dx, node.1 + dy);
+                if !grid.is_free(next.0, next.1) {
+                    continue;
+                }
+
+                // connect to the parent directly if it is visible
+                let node_parent = parent[&node];
+                let (from, from_g) = if grid.line_of_sight(node_parent, next) {
+                    (node_parent, g[&node_parent])
+                } else {
+                    (node, node_g)
+                };
+                let next_g = from_g + node_distance(from, next);
+                if g.get(&next).is_none_or(|old| next_g < *old) {
+                    g.insert(next, next_g);
+                    parent.insert(next, from);
+                    open.push(OpenNode {
+                        cost: next_g + node_distance(next, goal_node),
+                        node: next,
+                    });
+                }
+            }
+        }
+    }
+
+    if !found {
+        return Err("No water path found".to_owned());
+    }
+
+    // walk back
+    let mut nodes = vec![goal_node];
+    let mut current = goal_node;
+    while current != start_node {
+        current = parent[&current];
+        nodes.push(current);
+    }
+    nodes.reverse();
+
+    let mut path = vec![start];
+    path.extend(
+        nodes
+            .into_iter()
+            .map(|n| dimensions.canvas_to_engine(grid.node_to_canvas(n))),
+    );
+    path.push(goal);
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::height_at_engine;
+
+    /// 3x3 cells of deep water with a land wall down the middle
+    /// that leaves a gap of `gap` vertices at the bottom
+    fn water_with_wall(gap: usize) -> (Vec<f32>, Dimensions) {
+        let dimensions = Dimensions {
+            min_x: 0,
+            min_y: 0,
+            max_x: 2,
+            max_y: 2,
+            min_z: -2048.0,
+            max_z: 2048.0,
+        };
+        let stride = dimensions.stride(VERTEX_CNT);
+        let mut heights = vec![-1024.0; dimensions.pixel_size(VERTEX_CNT)];
+        for y in 0..dimensions.pixel_height(VERTEX_CNT) - gap {
+            for x in stride / 2 - 4..=stride / 2 + 4 {
+                heights[y * stride + x] = 512.0;
+            }
+        }
+        (heights, dimensions)
+    }
+
+    fn settings() -> WaterPathSettings {
+        WaterPathSettings {
+            clearance: 0.0,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn straight_path_over_open_water() {
+        let (heights, dimensions) = water_with_wall(VERTEX_CNT * 3);
+        let start = Pos2::new(CELL_WIDTH * 0.5, CELL_WIDTH * 1.5);
+        let goal = Pos2::new(CELL_WIDTH * 2.5, CELL_WIDTH * 1.5);
+        let path = find_water_path(&heights, &dimensions, start, goal, &settings()).unwrap();
+
+        assert_eq!(path.first(), Some(&start));
+        assert_eq!(path.last(), Some(&goal));
+        let length = crate::views::measure::polyline_length(&path, false);
+        assert!(length < start.distance(goal) + VERTEX_SPACING * 8.0);
+    }
+
+    #[test]
+    fn path_goes_around_land() {
+        let (heights, dimensions) = water_with_wall(VERTEX_CNT / 2);
+        let start = Pos2::new(CELL_WIDTH * 0.5, CELL_WIDTH * 2.5);
+        let goal = Pos2::new(CELL_WIDTH * 2.5, CELL_WIDTH * 2.5);
+        // the clearance keeps corners from being cut between grid nodes
+        let settings = WaterPathSettings::default();
+        let path = find_water_path(&heights, &dimensions, start, goal, &settings).unwrap();
+
+        // down to the gap and back up
+        let length = crate::views::measure::polyline_length(&path, false);
+        assert!(length > start.distance(goal) * 2.0);
+        for pair in path.windows(2) {
+            for i in 0..=16 {
+                let pos = pair[0].lerp(pair[1], i as f32 / 16.0);
+                let z = height_at_engine(&heights, &dimensions, pos);
+                assert!(z.is_none_or(|z| z < 0.0), "{:?} is on land", pos);
+            }
+        }
+    }
+
+    #[test]
+    fn no_path_through_closed_wall() {
+        let (heights, dimensions) = water_with_wall(0);
+        let start = Pos2::new(CELL_WIDTH * 0.5, CELL_WIDTH * 1.5);
+        let goal = Pos2::new(CELL_WIDTH * 2.5, CELL_WIDTH * 1.5);
+        assert_eq!(
+            find_water_path(&heights, &dimensions, start, goal, &settings()),
+            Err("No water path found".to_owned())
+        );
+        assert!(find_water_path(&[], &dimensions, start, goal, &settings()).is_err());
+    }
+}
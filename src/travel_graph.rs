@@ -0,0 +1,250 @@
+use std::collections::{BTreeSet, HashMap, HashSet, VecDeque};
+
+use tes3::esp::Cell;
+
+use crate::{CellKey, TravelEdge};
+
+/// A connection between two towns
+#[derive(Debug, Clone, PartialEq)]
+pub struct GraphEdge {
+    pub from: String,
+    pub to: String,
+    pub class: String,
+    pub npc_id: String,
+}
+
+/// Travel network with the nodes grouped by town
+#[derive(Debug, Clone, Default)]
+pub struct TravelGraph {
+    pub nodes: BTreeSet<String>,
+    pub edges: Vec<GraphEdge>,
+}
+
+/// Name of the town a cell belongs to, unnamed cells are their own node
+pub fn get_town_name(cell_records: &HashMap<CellKey, Cell>, key: CellKey) -> String {
+    if let Some(cell) = cell_records.get(&key) {
+        if !cell.name.is_empty() {
+            return cell.name.clone();
+        }
+        if let Some(region) = &cell.region {
+            return format!("{} ({},{})", region, key.0, key.1);
+        }
+    }
+    format!("Wilderness ({},{})", key.0, key.1)
+}
+
+impl TravelGraph {
+    /// Interior destinations are already resolved to their exterior door when loading
+    pub fn new(
+        travel_edges: &HashMap<String, Vec<TravelEdge>>,
+        cell_records: &HashMap<CellKey, Cell>,
+    ) -> Self {
+        let mut graph = TravelGraph::default();
+        let mut seen: HashSet<(String, String, String)> = HashSet::default();
+
+        for edge in travel_edges.values().flatten() {
+            let from = get_town_name(cell_records, edge.start_cell());
+            let to = get_town_name(cell_records, edge.destination_cell());
+            graph.nodes.insert(from.clone());
+            graph.nodes.insert(to.clone());
+
+            // several NPCs of the same class in a town offer the same connection
+            if seen.insert((from.clone(), to.clone(), edge.class.clone())) {
+                graph.edges.push(GraphEdge {
+                    from,
+                    to,
+                    class: edge.class.clone(),
+                    npc_id: edge.npc_id.clone(),
+                });
+            }
+        }
+
+        graph
+    }
+
+    fn outgoing(&self, node: &str) -> Vec<&GraphEdge> {
+        self.edges
+            .iter()
+            .filter(|e| e.from == node && e.to != node)
+            .collect()
+    }
+
+    /// Breadth first search for the trip with the fewest hops
+    pub fn shortest_path(&self, from: &str, to: &str) -> Option<Vec<&GraphEdge>> {
+        if from == to {
+            return Some(vec![]);
+        }
+
+        let mut previous: HashMap<&str, &GraphEdge> = HashMap::default();
+        let mut queue = VecDeque::from([from]);
+
+        while let Some(node) = queue.pop_front() {
+            for edge in self.outgoing(node) {
+                if edge.to == from || previous.contains_key(edge.to.as_str()) {
+                    continue;
+                }
+                previous.insert(edge.to.as_str(), edge);
+
+                if edge.to == to {
+                    // walk back to the start
+                    let mut path = vec![edge];
+                    let mut current = edge.from.as_str();
+                    while current != from {
+                        let edge = previous[current];
+                        path.push(edge);
+                        current = edge.from.as_str();
+                    }
+                    path.reverse();
+                    return Some(path);
+                }
+
+                queue.push_back(edge.to.as_str());
+            }
+        }
+
+        None
+    }
+
+    /// All towns that can not be reached from `from`
+    pub fn unreachable_from(&self, from: &str) -> Vec<&String> {
+        let mut visited: BTreeSet<&str> = BTreeSet::from([from]);
+        let mut queue = VecDeque::from([from]);
+        while let Some(node) = queue.pop_front() {
+            for edge in self.outgoing(node) {
+                if visited.insert(edge.to.as_str()) {
+                    queue.push_back(edge.to.as_str());
+                }
+            }
+        }
+
+        self.nodes
+            .iter()
+            .filter(|n| !visited.contains(n.as_str()))
+            .collect()
+    }
+
+    /// Towns that have no incoming connection at all
+    pub fn without_incoming(&self) -> Vec<&String> {
+        self.nodes
+            .iter()
+            .filter(|n| !self.edges.iter().any(|e| e.to == **n && e.from != **n))
+            .collect()
+    }
+
+    /// Connections without any service going back
+    pub fn one_way_edges(&self) -> Vec<&GraphEdge> {
+        self.edges
+            .iter()
+            .filter(|e| e.from != e.to)
+            .filter(|e| !self.edges.iter().any(|r| r.from == e.to && r.to == e.from))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn edge(from: &str, to: &str) -> GraphEdge {
+        GraphEdge {
+            from: from.to_string(),
+            to: to.to_string(),
+            class: "Caravaner".to_string(),
+            npc_id: format!("{}_guide", from),
+        }
+    }
+
+    fn graph(edges: Vec<GraphEdge>) -> TravelGraph {
+        let nodes = edges
+            .iter()
+            .flat_map(|e| [e.from.clone(), e.to.clone()])
+            .collect();
+        TravelGraph { nodes, edges }
+    }
+
+    #[test]
+    fn new_merges_services_per_town_and_class() {
+        let cell_records = HashMap::from([
+            (
+                (0, 0),
+                Cell {
+                    name: "Balmora".to_string(),
+                    ..Default::default()
+                },
+            ),
+            (
+                (1, 0),
+                Cell {
+                    name: "Suran".to_string(),
+                    ..Default::default()
+                },
+            ),
+        ]);
+        let travel_edge = |npc_id: &str, start: [f32; 3]| TravelEdge {
+            npc_id: npc_id.to_string(),
+            npc_name: String::new(),
+            class: "Caravaner".to_string(),
+            start,
+            destination: [8192.0 + 100.0, 100.0, 0.0],
+            interior: None,
+            npc_interior: None,
+        };
+        let travel_edges = HashMap::from([(
+            "Caravaner".to_string(),
+            vec![
+                travel_edge("a", [100.0, 100.0, 0.0]),
+                travel_edge("b", [200.0, 200.0, 0.0]),
+            ],
+        )]);
+
+        let graph = TravelGraph::new(&travel_edges, &cell_records);
+        assert_eq!(graph.nodes.len(), 2);
+        assert_eq!(graph.edges.len(), 1);
+        assert_eq!(graph.edges[0].from, "Balmora");
+        assert_eq!(graph.edges[0].to, "Suran");
+    }
+
+    #[test]
+    fn shortest_path_takes_fewest_hops() {
+        let graph = graph(vec![
+            edge("a", "b"),
+            edge("b", "c"),
+            edge("c", "d"),
+            edge("a", "c"),
+            edge("d", "a"),
+        ]);
+
+        let path = graph.shortest_path("a", "d").unwrap();
+        let hops: Vec<_> = path
+            .iter()
+            .map(|e| (e.from.as_str(), e.to.as_str()))
+            .collect();
+        assert_eq!(hops, vec![("a", "c"), ("c", "d")]);
+        assert_eq!(graph.shortest_path("a", "a").unwrap().len(), 0);
+    }
+
+    #[test]
+    fn shortest_path_without_connection() {
+        let graph = graph(vec![edge("a", "b"), edge("c", "a")]);
+        assert!(graph.shortest_path("a", "c").is_none());
+        assert_eq!(graph.unreachable_from("a"), vec!["c"]);
+    }
+
+    #[test]
+    fn one_way_edges_and_dead_ends() {
+        let graph = graph(vec![
+            edge("a", "b"),
+            edge("b", "a"),
+            edge("b", "c"),
+            edge("d", "a"),
+        ]);
+
+        let one_way: Vec<_> = graph
+            .one_way_edges()
+            .iter()
+            .map(|e| (e.from.as_str(), e.to.as_str()))
+            .collect();
+        assert_eq!(one_way, vec![("b", "c"), ("d", "a")]);
+        assert_eq!(graph.without_incoming(), vec!["d"]);
+    }
+}
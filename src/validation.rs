@@ -0,0 +1,132 @@
+use std::collections::HashMap;
+
+use egui::Color32;
+use tes3::esp::{Cell, Landscape, Region};
+
+use crate::{overlay::regions::find_region, CellKey};
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ECellIssue {
+    /// exterior cell without a region
+    NoRegion,
+    /// the region id is not in any loaded plugin
+    UnknownRegion(String),
+    /// LAND record without a matching CELL
+    LandWithoutCell,
+}
+
+impl ECellIssue {
+    pub fn color(&self) -> Color32 {
+        match self {
+            ECellIssue::NoRegion => Color32::from_rgb(255, 165, 0),
+            ECellIssue::UnknownRegion(_) => Color32::RED,
+            ECellIssue::LandWithoutCell => Color32::from_rgb(255, 0, 255),
+        }
+    }
+
+    pub fn description(&self) -> String {
+        match self {
+            ECellIssue::NoRegion => "No region".to_owned(),
+            ECellIssue::UnknownRegion(id) => format!("Unknown region: {}", id),
+            ECellIssue::LandWithoutCell => "LAND without CELL".to_owned(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct CellIssue {
+    pub key: CellKey,
+    pub issue: ECellIssue,
+}
+
+/// Check the exterior cells for missing regions and LAND records for missing cells
+pub fn validate_cells(
+    cell_records: &HashMap<CellKey, Cell>,
+    land_records: &HashMap<CellKey, Landscape>,
+    regn_records: &HashMap<String, Region>,
+) -> Vec<CellIssue> {
+    let mut issues = vec![];
+
+    for (key, cell) in cell_records.iter() {
+        match cell.region.as_ref().filter(|r| !r.is_empty()) {
+            None => issues.push(CellIssue {
+                key: *key,
+                issue: ECellIssue::NoRegion,
+            }),
+            Some(region) if find_region(regn_records, region).is_none() => issues.push(CellIssue {
+                key: *key,
+                issue: ECellIssue::UnknownRegion(region.clone()),
+            }),
+            _ => {}
+        }
+    }
+
+    for key in land_records.keys() {
+        if !cell_records.contains_key(key) {
+            issues.push(CellIssue {
+                key: *key,
+                issue: ECellIssue::LandWithoutCell,
+            });
+        }
+    }
+
+    issues.sort();
+    issues
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cell(region: Option<&str>) -> Cell {
+        Cell {
+            region: region.map(|r| r.to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn validate_cells_finds_region_and_land_issues() {
+        let cell_records = HashMap::from([
+            ((0, 0), cell(Some("Bitter Coast Region"))),
+            ((1, 0), cell(None)),
+            ((2, 0), cell(Some(""))),
+            ((3, 0), cell(Some("Missing Region"))),
+        ]);
+        let land_records = HashMap::from([
+            ((0, 0), Landscape::default()),
+            ((5, 5), Landscape::default()),
+        ]);
+        // region ids are matched without case
+        let regn_records = HashMap::from([(
+            "bitter coast region".to_string(),
+            Region {
+                id: "bitter coast region".to_string(),
+                ..Default::default()
+            },
+        )]);
+
+        let issues = validate_cells(&cell_records, &land_records, &regn_records);
+        assert_eq!(
+            issues,
+            vec![
+                CellIssue {
+                    key: (1, 0),
+                    issue: ECellIssue::NoRegion,
+                },
+                CellIssue {
+                    key: (2, 0),
+                    issue: ECellIssue::NoRegion,
+                },
+                CellIssue {
+                    key: (3, 0),
+                    issue: ECellIssue::UnknownRegion("Missing Region".to_string()),
+                },
+                CellIssue {
+                    key: (5, 5),
+                    issue: ECellIssue::LandWithoutCell,
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,151 @@
+use std::fs;
+
+use log::{error, info};
+use serde::{Deserialize, Serialize};
+
+use crate::{Bookmark, TemplateApp};
+
+/// Bookmarks file that can be shared
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+struct BookmarksFile {
+    bookmarks: Vec<Bookmark>,
+}
+
+impl TemplateApp {
+    pub fn bookmarks_panel(&mut self, ui: &mut egui::Ui, ctx: &egui::Context) {
+        ui.heading("Bookmarks");
+
+        // add
+        ui.horizontal(|ui| {
+            ui.text_edit_singleline(&mut self.runtime_data.bookmark_name);
+            let name = self.runtime_data.bookmark_name.trim().to_owned();
+            if ui
+                .add_enabled(!name.is_empty(), egui::Button::new("Add"))
+                .clicked()
+            {
+                let bookmark = self.create_bookmark(name, self.runtime_data.bookmark_view);
+                self.add_bookmark(bookmark);
+                self.runtime_data.bookmark_name.clear();
+            }
+        });
+        ui.checkbox(
+            &mut self.runtime_data.bookmark_view,
+            "Include background and overlays",
+        );
+
+        // import and export
+        ui.horizontal(|ui| {
+            if ui.button("Import").clicked() {
+                if let Some(path) = rfd::FileDialog::new()
+                    .add_filter("toml", &["toml"])
+                    .pick_file()
+                {
+                    match fs::read_to_string(&path)
+                        .map_err(|e| e.to_string())
+                        .and_then(|s| {
+                            toml::from_str::<BookmarksFile>(&s).map_err(|e| e.to_string())
+                        }) {
+                        Ok(file) => {
+                            info!("Imported {} bookmarks", file.bookmarks.len());
+                            for bookmark in file.bookmarks {
+                                self.add_bookmark(bookmark);
+                            }
+                        }
+                        Err(e) => error!("Error importing bookmarks {}: {}", path.display(), e),
+                    }
+                }
+            }
+            if ui
+                .add_enabled(
+                    !self.ui_data.bookmarks.is_empty(),
+                    egui::Button::new("Export"),
+                )
+                .clicked()
+            {
+                if let Some(path) = rfd::FileDialog::new()
+                    .add_filter("toml", &["toml"])
+                    .set_file_name("bookmarks.toml")
+                    .save_file()
+                {
+                    let file = BookmarksFile {
+                        bookmarks: self.ui_data.bookmarks.clone(),
+                    };
+                    match toml::to_string_pretty(&file)
+                        .map_err(|e| e.to_string())
+                        .and_then(|s| fs::write(&path, s).map_err(|e| e.to_string()))
+                    {
+                        Ok(_) => {
+                            info!("Exported bookmarks to {}", path.display());
+                            rfd::MessageDialog::new()
+                                .set_title("Info")
+                                .set_description(format!(
+                                    "Exported bookmarks to {}",
+                                    path.display()
+                                ))
+                                .set_buttons(rfd::MessageButtons::Ok)
+                                .show();
+                        }
+                        Err(e) => error!("Error exporting bookmarks {}: {}", path.display(), e),
+                    }
+                }
+            }
+        });
+
+        ui.separator();
+
+        // list
+        let mut goto = None;
+        let mut remove = None;
+        egui::ScrollArea::vertical()
+            .auto_shrink([false, true])
+            .show(ui, |ui| {
+                for (i, bookmark) in self.ui_data.bookmarks.iter().enumerate() {
+                    ui.horizontal(|ui| {
+                        if ui.small_button("x").on_hover_text("Remove").clicked() {
+                            remove = Some(i);
+                        }
+                        let text = if bookmark.view.is_some() {
+                            format!("{} *", bookmark.name)
+                        } else {
+                            bookmark.name.clone()
+                        };
+                        let label = egui::Label::new(text).sense(egui::Sense::click());
+                        if ui
+                            .add(label)
+                            .on_hover_text(format!(
+                                "({:.0}, {:.0}) {:.0} x {:.0}",
+                                bookmark.center[0],
+                                bookmark.center[1],
+                                bookmark.extent[0],
+                                bookmark.extent[1]
+                            ))
+                            .clicked()
+                        {
+                            goto = Some(bookmark.clone());
+                        }
+                    });
+                }
+            });
+
+        if let Some(i) = remove {
+            self.ui_data.bookmarks.remove(i);
+        }
+        if let Some(bookmark) = goto {
+            self.apply_bookmark(ctx, &bookmark);
+        }
+    }
+
+    /// Add a bookmark, replacing an existing one with the same name
+    fn add_bookmark(&mut self, bookmark: Bookmark) {
+        if let Some(existing) = self
+            .ui_data
+            .bookmarks
+            .iter_mut()
+            .find(|b| b.name == bookmark.name)
+        {
+            *existing = bookmark;
+        } else {
+            self.ui_data.bookmarks.push(bookmark);
+        }
+    }
+}
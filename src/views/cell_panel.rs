@@ -23,13 +23,43 @@ impl TemplateApp {
 
         ui.separator();
 
+        // validation results
+        egui::CollapsingHeader::new(format!("Issues ({})", self.cell_issues.len()))
+            .id_salt("cell_issues")
+            .show(ui, |ui| {
+                egui::ScrollArea::vertical()
+                    .id_salt("cell_issues_scroll")
+                    .max_height(200.0)
+                    .show(ui, |ui| {
+                        let mut focus = None;
+                        for issue in self.cell_issues.iter() {
+                            let text = egui::RichText::new(format!(
+                                "{:?} - {}",
+                                issue.key,
+                                issue.issue.description()
+                            ))
+                            .color(issue.issue.color());
+                            let label = egui::Label::new(text).sense(egui::Sense::click());
+                            if ui.add(label).on_hover_text("Show on map").clicked() {
+                                focus = Some(issue.key);
+                            }
+                        }
+                        if let Some(key) = focus {
+                            self.runtime_data.selected_ids = vec![key];
+                            self.focus_cell(key);
+                        }
+                    });
+            });
+
+        ui.separator();
+
         // search bar
         ui.horizontal(|ui| {
             ui.label("Filter: ");
             ui.text_edit_singleline(&mut self.runtime_data.cell_filter);
             // clear filter button
             if ui.button("x").clicked() {
-                self.runtime_data.plugin_filter.clear();
+                self.runtime_data.cell_filter.clear();
             }
         });
 
@@ -0,0 +1,112 @@
+use egui::{emath::RectTransform, Color32, Pos2};
+
+use crate::overlay::doors::{get_door_at, is_interior_door};
+use crate::{cell_key_from_engine, TemplateApp};
+
+impl TemplateApp {
+    /// Window listing the doors of the selected interior
+    pub fn doors_window(&mut self, ctx: &egui::Context) {
+        let Some(name) = self.runtime_data.selected_interior.clone() else {
+            return;
+        };
+
+        let mut open = true;
+        egui::Window::new(format!("Doors: {}", name))
+            .id(egui::Id::new("doors_window"))
+            .open(&mut open)
+            .resizable(true)
+            .show(ctx, |ui| {
+                let key = name.to_lowercase();
+                if !self.interior_records.contains_key(&key) {
+                    ui.colored_label(Color32::RED, "Interior not found in the loaded plugins");
+                    return;
+                }
+
+                if ui.button("Show plan").clicked() {
+                    self.open_interior(&name);
+                }
+
+                let mut doors = self
+                    .interior_references
+                    .get(&key)
+                    .map(|refs| {
+                        refs.iter()
+                            .filter(|r| r.destination.is_some())
+                            .collect::<Vec<_>>()
+                    })
+                    .unwrap_or_default();
+                doors.sort_by(|a, b| a.id.cmp(&b.id));
+
+                if doors.is_empty() {
+                    ui.label("No doors");
+                    return;
+                }
+
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    for door in doors {
+                        let Some(destination) = &door.destination else {
+                            continue;
+                        };
+
+                        ui.horizontal(|ui| {
+                            ui.label(&door.id);
+                            ui.label("→");
+                            if is_interior_door(door) {
+                                // doors to other interiors can be followed
+                                let found = self
+                                    .interior_records
+                                    .contains_key(&destination.cell.to_lowercase());
+                                let text = egui::RichText::new(&destination.cell).color(if found {
+                                    Color32::LIGHT_BLUE
+                                } else {
+                                    Color32::RED
+                                });
+                                if ui.link(text).clicked() {
+                                    self.runtime_data.selected_interior =
+                                        Some(destination.cell.clone());
+                                }
+                            } else {
+                                let cell = cell_key_from_engine(
+                                    destination.translation[0],
+                                    destination.translation[1],
+                                );
+                                ui.label(format!(
+                                    "Exterior {:?} ({:.0}, {:.0})",
+                                    cell, destination.translation[0], destination.translation[1]
+                                ));
+                            }
+                        });
+                    }
+                });
+            });
+
+        if !open {
+            self.runtime_data.selected_interior = None;
+        }
+    }
+
+    pub fn doors_on_click(&mut self, from_screen: RectTransform, interact_pos: Pos2) -> bool {
+        if !self.ui_data.overlay_doors {
+            return false;
+        }
+
+        let key = self.cellkey_from_screen(from_screen, interact_pos);
+        let door = get_door_at(
+            from_screen.inverse(),
+            &self.dimensions,
+            &self.reference_records,
+            key,
+            interact_pos,
+            6.0,
+        );
+
+        if let Some(door) = door.filter(|d| is_interior_door(d)) {
+            if let Some(destination) = &door.destination {
+                self.runtime_data.selected_interior = Some(destination.cell.clone());
+                return true;
+            }
+        }
+
+        false
+    }
+}
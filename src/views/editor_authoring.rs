@@ -0,0 +1,838 @@
+use egui::{emath::RectTransform, Pos2};
+
+use crate::views::editor_panel::{EditorData, Port, PortData, Pos3, RouteId, Segment};
+use crate::TemplateApp;
+
+/// What a plain click on the map does in the editor
+#[derive(Debug, Clone, Default, PartialEq)]
+pub enum EEditorTool {
+    #[default]
+    Select,
+    /// append clicked points to the current segment
+    DrawSegment,
+    /// place a port position for a service
+    PlacePort {
+        port: String,
+        service: String,
+        field: EPortField,
+    },
+    /// place the start (0) or end (1) of the water pathfinder
+    PickPathPoint(usize),
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum EPortField {
+    #[default]
+    Position,
+    PositionStart,
+    PositionEnd,
+}
+
+impl EditorData {
+    /// A segment id that is not used yet
+    pub fn unique_segment_id(&self, base: &str) -> String {
+        if !self.segments.contains_key(base) {
+            return base.to_owned();
+        }
+        (2..)
+            .map(|i| format!("{}_{}", base, i))
+            .find(|id| !self.segments.contains_key(id))
+            .unwrap_or_default()
+    }
+
+    fn segment_points(&self, id: &str) -> Vec<Pos3> {
+        self.segments
+            .get(id)
+            .and_then(|s| s.route1.clone())
+            .unwrap_or_default()
+    }
+
+    pub fn create_segment(&mut self, id: &str) {
+        let id = self.unique_segment_id(id);
+        self.begin_edit();
+        self.segments.insert(
+            id.clone(),
+            Segment {
+                id: id.clone(),
+                route1: Some(vec![]),
+                selected: true,
+            },
+        );
+        self.commit_edit("Create segment");
+        self.current_segment = Some(id);
+        self.tool = EEditorTool::DrawSegment;
+    }
+
+    /// Split a segment at a point, both parts share the point
+    pub fn split_segment(&mut self, id: &str, index: usize) {
+        let points = self.segment_points(id);
+        if index == 0 || index + 1 >= points.len() {
+            return;
+        }
+
+        let new_id = self.unique_segment_id(id);
+        self.begin_edit();
+        if let Some(segment) = self.segments.get_mut(id) {
+            segment.route1 = Some(points[..=index].to_vec());
+        }
+        self.segments.insert(
+            new_id.clone(),
+            Segment {
+                id: new_id.clone(),
+                route1: Some(points[index..].to_vec()),
+                selected: true,
+            },
+        );
+        // routes keep going through both parts, back to front so the indices stay valid
+        for route in self.routes.iter_mut() {
+            let positions = route
+                .segments
+                .iter()
+                .enumerate()
+                .filter(|(_, s)| *s == id)
+                .map(|(i, _)| i)
+                .collect::<Vec<_>>();
+            for pos in positions.into_iter().rev() {
+                route.segments.insert(pos + 1, new_id.clone());
+            }
+        }
+        self.commit_edit("Split segment");
+        self.picked_point = None;
+    }
+
+    /// Append `other` to `id`, reversing it if its end is the closer one
+    pub fn join_segments(&mut self, id: &str, other: &str) {
+        if id == other {
+            return;
+        }
+        let mut points = self.segment_points(id);
+        let mut other_points = self.segment_points(other);
+        let (Some(end), Some(first), Some(last)) =
+            (points.last(), other_points.first(), other_points.last())
+        else {
+            return;
+        };
+
+        let end = Pos2::new(end.x, end.y);
+        if end.distance(Pos2::new(last.x, last.y)) < end.distance(Pos2::new(first.x, first.y)) {
+            other_points.reverse();
+        }
+        // don't duplicate the shared joint
+        if other_points
+            .first()
+            .is_some_and(|p| end.distance(Pos2::new(p.x, p.y)) < 1.0)
+        {
+            other_points.remove(0);
+        }
+        points.extend(other_points);
+
+        self.begin_edit();
+        if let Some(segment) = self.segments.get_mut(id) {
+            segment.route1 = Some(points);
+        }
+        self.segments.remove(other);
+        for route in self.routes.iter_mut() {
+            route.segments.retain(|s| s != other);
+        }
+        self.commit_edit("Join segments");
+        self.picked_point = None;
+    }
+
+    pub fn rename_segment(&mut self, id: &str, new_id: &str) {
+        let new_id = new_id.trim();
+        if new_id.is_empty() || new_id == id || self.segments.contains_key(new_id) {
+            return;
+        }
+
+        self.begin_edit();
+        if let Some(mut segment) = self.segments.remove(id) {
+            segment.id = new_id.to_owned();
+            self.segments.insert(new_id.to_owned(), segment);
+        }
+        for route in self.routes.iter_mut() {
+            for segment in route.segments.iter_mut().filter(|s| *s == id) {
+                *segment = new_id.to_owned();
+            }
+        }
+        self.commit_edit("Rename segment");
+        if self.current_segment.as_deref() == Some(id) {
+            self.current_segment = Some(new_id.to_owned());
+        }
+        self.picked_point = None;
+    }
+
+    /// Remove a segment and take it out of all routes
+    pub fn delete_segment(&mut self, id: &str) {
+        if !self.segments.contains_key(id) {
+            return;
+        }
+
+        self.begin_edit();
+        self.segments.remove(id);
+        for route in self.routes.iter_mut() {
+            route.segments.retain(|s| s != id);
+        }
+        self.commit_edit("Delete segment");
+        if self.current_segment.as_deref() == Some(id) {
+            self.current_segment = None;
+        }
+        self.picked_point = None;
+        self.selected_points.clear();
+    }
+
+    pub fn delete_route(&mut self, id: &RouteId) {
+        self.begin_edit();
+        self.routes.retain(|r| r.id != *id);
+        self.commit_edit("Delete route");
+        self.routes_metadata.remove(id);
+    }
+
+    /// Change the id of a route, an existing route with the new id is replaced
+    pub fn rename_route(&mut self, id: &RouteId, new_id: RouteId) {
+        if new_id == *id
+            || new_id.start.is_empty()
+            || new_id.destination.is_empty()
+            || new_id.service.is_empty()
+        {
+            return;
+        }
+
+        self.begin_edit();
+        self.routes.retain(|r| r.id != new_id);
+        for route in self.routes.iter_mut().filter(|r| r.id == *id) {
+            route.id = new_id.clone();
+        }
+        self.commit_edit("Rename route");
+        if let Some(metadata) = self.routes_metadata.remove(id) {
+            self.routes_metadata.insert(new_id, metadata);
+        }
+    }
+
+    /// Remove a port, routes from or to it are kept and show up in the validation
+    pub fn delete_port(&mut self, name: &str) {
+        self.begin_edit();
+        self.ports.remove(name);
+        self.commit_edit("Delete port");
+    }
+
+    /// Rename a port and the routes starting or ending there
+    pub fn rename_port(&mut self, name: &str, new_name: &str) {
+        let new_name = new_name.trim();
+        if new_name.is_empty() || new_name == name || self.ports.contains_key(new_name) {
+            return;
+        }
+
+        self.begin_edit();
+        if let Some(port) = self.ports.remove(name) {
+            self.ports.insert(new_name.to_owned(), port);
+        }
+        for route in self.routes.iter_mut() {
+            if route.id.start == name {
+                route.id.start = new_name.to_owned();
+            }
+            if route.id.destination == name {
+                route.id.destination = new_name.to_owned();
+            }
+        }
+        self.commit_edit("Rename port");
+        // the metadata is keyed by the old route ids
+        self.routes_metadata.clear();
+    }
+}
+
+impl PortData {
+    /// End of the heading line drawn from the port position, also the rotation handle
+    pub fn heading_tip(&self) -> Pos2 {
+        // rotations are in radians clockwise from north
+        let z = self.rotation.z;
+        Pos2::new(self.position.x, self.position.y) + egui::vec2(z.sin(), z.cos()) * 512.0
+    }
+}
+
+impl TemplateApp {
+    /// Port and service whose rotation handle is under the screen position
+    pub fn editor_port_handle_at(
+        &self,
+        from_screen: RectTransform,
+        screen_pos: Pos2,
+    ) -> Option<(String, String)> {
+        let to_screen = from_screen.inverse();
+        self.editor_data
+            .ports
+            .iter()
+            .flat_map(|(name, port)| port.data.iter().map(move |(s, d)| (name, s, d)))
+            .map(|(name, service, data)| {
+                let tip = to_screen * self.dimensions.engine_to_canvas(data.heading_tip());
+                (name, service, tip.distance(screen_pos))
+            })
+            .filter(|(_, _, d)| *d < 8.0)
+            .min_by(|a, b| a.2.total_cmp(&b.2))
+            .map(|(name, service, _)| (name.clone(), service.clone()))
+    }
+
+    /// Turn the port heading towards the engine position while its handle is dragged
+    pub fn editor_rotate_port(&mut self, engine_pos: Pos2) {
+        let Some((name, service)) = self.editor_data.rotating_port.clone() else {
+            return;
+        };
+        let Some(data) = self
+            .editor_data
+            .ports
+            .get_mut(&name)
+            .and_then(|p| p.data.get_mut(&service))
+        else {
+            return;
+        };
+        let delta = engine_pos - Pos2::new(data.position.x, data.position.y);
+        if delta.length() > 1.0 {
+            data.rotation.z = delta.x.atan2(delta.y);
+        }
+    }
+
+    /// Handle a click with the active editor tool, returns true if captured
+    pub fn editor_tool_on_click(&mut self, from_screen: RectTransform, interact_pos: Pos2) -> bool {
+        let engine_pos = self.dimensions.canvas_to_engine(from_screen * interact_pos);
+
+        match self.editor_data.tool.clone() {
+            EEditorTool::Select => false,
+            EEditorTool::DrawSegment => {
+                let Some(id) = self.editor_data.current_segment.clone() else {
+                    return false;
+                };
+                let z = self.editor_point_z(&id, engine_pos);
+                self.editor_data.begin_edit();
+                if let Some(segment) = self.editor_data.segments.get_mut(&id) {
+                    segment.route1.get_or_insert_with(Vec::new).push(Pos3::new(
+                        engine_pos.x,
+                        engine_pos.y,
+                        z,
+                    ));
+                }
+                self.editor_data.commit_edit("Add point");
+                true
+            }
+            EEditorTool::PlacePort {
+                port,
+                service,
+                field,
+            } => {
+                let z = if self.editor_data.terrain_settings.auto_z {
+                    self.editor_height_for_service(Some(&service), engine_pos)
+                } else {
+                    0.0
+                };
+                self.editor_data.begin_edit();
+                let data = self
+                    .editor_data
+                    .ports
+                    .entry(port)
+                    .or_default()
+                    .data
+                    .entry(service)
+                    .or_default();
+                let position = Pos3::new(engine_pos.x, engine_pos.y, z);
+                match field {
+                    EPortField::Position => data.position = position,
+                    EPortField::PositionStart => data.positionStart = Some(position),
+                    EPortField::PositionEnd => data.positionEnd = Some(position),
+                }
+                self.editor_data.commit_edit("Place port");
+                self.editor_data.tool = EEditorTool::Select;
+                true
+            }
+            EEditorTool::PickPathPoint(i) => {
+                if let Some(endpoint) = self.editor_data.path_endpoints.get_mut(i) {
+                    *endpoint = Some(engine_pos);
+                }
+                self.editor_data.tool = EEditorTool::Select;
+                true
+            }
+        }
+    }
+
+    /// Segment creation, splitting, joining and renaming
+    pub fn editor_segment_tools_ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.text_edit_singleline(&mut self.editor_data.new_name);
+            if ui.button("New segment").clicked() {
+                let name = self.editor_data.new_name.trim().to_owned();
+                let name = if name.is_empty() {
+                    "segment".to_owned()
+                } else {
+                    name
+                };
+                self.editor_data.create_segment(&name);
+            }
+        });
+
+        let Some(id) = self.editor_data.current_segment.clone() else {
+            ui.label("Select a segment to edit it");
+            return;
+        };
+
+        let mut drawing = self.editor_data.tool == EEditorTool::DrawSegment;
+        if ui
+            .toggle_value(&mut drawing, "✏ Draw points")
+            .on_hover_text("Click on the map to append points to the current segment")
+            .changed()
+        {
+            self.editor_data.tool = if drawing {
+                EEditorTool::DrawSegment
+            } else {
+                EEditorTool::Select
+            };
+        }
+
+        // rename and delete
+        ui.horizontal(|ui| {
+            if ui.button("Rename to").clicked() {
+                let new_id = self.editor_data.new_name.clone();
+                self.editor_data.rename_segment(&id, &new_id);
+            }
+            ui.label("the name above");
+        });
+        if ui
+            .button("Delete segment")
+            .on_hover_text("Also removes it from all routes")
+            .clicked()
+        {
+            self.editor_data.delete_segment(&id);
+            return;
+        }
+
+        // split
+        match self.editor_data.picked_point.clone() {
+            Some((point_segment, i)) if point_segment == id => {
+                if ui.button(format!("Split at point {}", i)).clicked() {
+                    self.editor_data.split_segment(&id, i);
+                }
+            }
+            _ => {
+                ui.label("Click a point of the segment to split it");
+            }
+        }
+
+        // join
+        ui.horizontal(|ui| {
+            let mut keys = self
+                .editor_data
+                .segments
+                .keys()
+                .filter(|k| **k != id)
+                .cloned()
+                .collect::<Vec<_>>();
+            keys.sort();
+            egui::ComboBox::from_id_salt("join_segment")
+                .selected_text(&self.editor_data.join_target)
+                .show_ui(ui, |ui| {
+                    for key in keys {
+                        ui.selectable_value(&mut self.editor_data.join_target, key.clone(), key);
+                    }
+                });
+            if ui
+                .add_enabled(
+                    self.editor_data
+                        .segments
+                        .contains_key(&self.editor_data.join_target),
+                    egui::Button::new("Join"),
+                )
+                .clicked()
+            {
+                let other = std::mem::take(&mut self.editor_data.join_target);
+                self.editor_data.join_segments(&id, &other);
+            }
+        });
+    }
+
+    /// Build a route from segments in order
+    pub fn editor_route_builder_ui(&mut self, ui: &mut egui::Ui) {
+        let mut ports = self.editor_data.ports.keys().cloned().collect::<Vec<_>>();
+        ports.sort();
+        let mut segments = self
+            .editor_data
+            .segments
+            .keys()
+            .cloned()
+            .collect::<Vec<_>>();
+        segments.sort();
+
+        let draft = &mut self.editor_data.route_draft;
+        egui::Grid::new("route_builder_grid")
+            .num_columns(2)
+            .show(ui, |ui| {
+                ui.label("Start:");
+                egui::ComboBox::from_id_salt("route_start")
+                    .selected_text(&draft.id.start)
+                    .show_ui(ui, |ui| {
+                        for port in ports.iter() {
+                            ui.selectable_value(&mut draft.id.start, port.clone(), port);
+                        }
+                    });
+                ui.end_row();
+
+                ui.label("Destination:");
+                egui::ComboBox::from_id_salt("route_destination")
+                    .selected_text(&draft.id.destination)
+                    .show_ui(ui, |ui| {
+                        for port in ports.iter() {
+                            ui.selectable_value(&mut draft.id.destination, port.clone(), port);
+                        }
+                    });
+                ui.end_row();
+
+                ui.label("Service:");
+                ui.text_edit_singleline(&mut draft.id.service);
+                ui.end_row();
+            });
+
+        // segments in order
+        let mut remove = None;
+        let mut move_up = None;
+        for (i, segment) in draft.segments.iter().enumerate() {
+            ui.horizontal(|ui| {
+                if ui.small_button("x").clicked() {
+                    remove = Some(i);
+                }
+                if ui
+                    .add_enabled(i > 0, egui::Button::new("⏶").small())
+                    .clicked()
+                {
+                    move_up = Some(i);
+                }
+                ui.label(format!("{}. {}", i + 1, segment));
+            });
+        }
+        if let Some(i) = remove {
+            draft.segments.remove(i);
+        }
+        if let Some(i) = move_up {
+            draft.segments.swap(i - 1, i);
+        }
+
+        egui::ComboBox::from_id_salt("route_add_segment")
+            .selected_text("Add segment")
+            .show_ui(ui, |ui| {
+                for segment in segments {
+                    if ui.selectable_label(false, &segment).clicked() {
+                        draft.segments.push(segment);
+                    }
+                }
+            });
+
+        let complete = !draft.id.start.is_empty()
+            && !draft.id.destination.is_empty()
+            && !draft.id.service.is_empty()
+            && !draft.segments.is_empty();
+        if ui
+            .add_enabled(complete, egui::Button::new("Create route"))
+            .clicked()
+        {
+            let route = std::mem::take(&mut self.editor_data.route_draft);
+            self.editor_data.begin_edit();
+            // replace a route with the same id
+            self.editor_data.routes.retain(|r| r.id != route.id);
+            self.editor_data.routes.push(route);
+            self.editor_data.commit_edit("Create route");
+        }
+    }
+
+    /// Create ports and edit their positions and rotations
+    pub fn editor_ports_ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.text_edit_singleline(&mut self.editor_data.new_port_name);
+            let name = self.editor_data.new_port_name.trim().to_owned();
+            if ui
+                .add_enabled(
+                    !name.is_empty() && !self.editor_data.ports.contains_key(&name),
+                    egui::Button::new("New port"),
+                )
+                .clicked()
+            {
+                self.editor_data.begin_edit();
+                self.editor_data.ports.insert(name, Port::default());
+                self.editor_data.commit_edit("Create port");
+                self.editor_data.new_port_name.clear();
+            }
+        });
+
+        let mut keys: Vec<String> = self.editor_data.ports.keys().cloned().collect();
+        keys.sort();
+
+        for name in keys {
+            ui.collapsing(&name, |ui| {
+                // rename and delete
+                ui.horizontal(|ui| {
+                    if ui
+                        .button("Rename to")
+                        .on_hover_text("Also renames the port in its routes")
+                        .clicked()
+                    {
+                        let new_name = std::mem::take(&mut self.editor_data.new_port_name);
+                        self.editor_data.rename_port(&name, &new_name);
+                    }
+                    ui.label("the name above");
+                    if ui.button("Delete port").clicked() {
+                        self.editor_data.delete_port(&name);
+                    }
+                });
+
+                // add a service
+                ui.horizontal(|ui| {
+                    ui.text_edit_singleline(&mut self.editor_data.new_port_service);
+                    let service = self.editor_data.new_port_service.trim().to_owned();
+                    if ui
+                        .add_enabled(!service.is_empty(), egui::Button::new("Add service"))
+                        .clicked()
+                    {
+                        // place the position on the map
+                        self.editor_data.tool = EEditorTool::PlacePort {
+                            port: name.clone(),
+                            service,
+                            field: EPortField::Position,
+                        };
+                        self.editor_data.new_port_service.clear();
+                    }
+                });
+
+                let Some(port) = self.editor_data.ports.get(&name).cloned() else {
+                    return;
+                };
+                let mut services = port.data.keys().cloned().collect::<Vec<_>>();
+                services.sort();
+
+                for service in services {
+                    let Some(mut data) = port.data.get(&service).cloned() else {
+                        continue;
+                    };
+                    ui.label(egui::RichText::new(&service).strong());
+                    let (changed, finished) = self.port_data_ui(ui, &name, &service, &mut data);
+                    if changed {
+                        // a no-op while a drag already started the edit
+                        self.editor_data.begin_edit();
+                        if let Some(port) = self.editor_data.ports.get_mut(&name) {
+                            port.data.insert(service.clone(), data);
+                        }
+                    }
+                    if finished {
+                        self.editor_data.commit_edit("Edit port");
+                    }
+                }
+            });
+        }
+    }
+
+    /// Positions are placed on the map, rotations edited here
+    /// Returns if the data changed and if the edit is finished, drags are one edit
+    fn port_data_ui(
+        &mut self,
+        ui: &mut egui::Ui,
+        port: &str,
+        service: &str,
+        data: &mut PortData,
+    ) -> (bool, bool) {
+        let mut changed = false;
+        let mut finished = false;
+        egui::Grid::new(format!("port_{}_{}", port, service))
+            .num_columns(3)
+            .show(ui, |ui| {
+                for (label, field) in [
+                    ("Position", EPortField::Position),
+                    ("Start", EPortField::PositionStart),
+                    ("End", EPortField::PositionEnd),
+                ] {
+                    let (position, rotation) = match field {
+                        EPortField::Position => (Some(&data.position), Some(&mut data.rotation)),
+                        EPortField::PositionStart => {
+                            (data.positionStart.as_ref(), data.rotationStart.as_mut())
+                        }
+                        EPortField::PositionEnd => {
+                            (data.positionEnd.as_ref(), data.rotationEnd.as_mut())
+                        }
+                    };
+
+                    ui.label(label);
+                    if ui
+                        .button(
+                            position
+                                .map(|p| p.to_string())
+                                .unwrap_or("Place".to_owned()),
+                        )
+                        .on_hover_text("Click to place on the map")
+                        .clicked()
+                    {
+                        self.editor_data.tool = EEditorTool::PlacePort {
+                            port: port.to_owned(),
+                            service: service.to_owned(),
+                            field,
+                        };
+                    }
+                    if let Some(rotation) = rotation {
+                        let response = ui.add(
+                            egui::DragValue::new(&mut rotation.z)
+                                .speed(0.01)
+                                .prefix("rot z: "),
+                        );
+                        if response.drag_started() || response.gained_focus() {
+                            self.editor_data.begin_edit();
+                        }
+                        changed |= response.changed();
+                        finished |= response.drag_stopped() || response.lost_focus();
+                    } else if position.is_some() {
+                        // the rotation is optional for the start and end positions
+                        if ui.button("Add rotation").clicked() {
+                            match field {
+                                EPortField::PositionStart => {
+                                    data.rotationStart = Some(Pos3::default())
+                                }
+                                EPortField::PositionEnd => data.rotationEnd = Some(Pos3::default()),
+                                EPortField::Position => {}
+                            }
+                            changed = true;
+                            finished = true;
+                        }
+                    }
+                    ui.end_row();
+                }
+
+                ui.label("Reverse start");
+                let mut reverse = data.reverseStart.unwrap_or(false);
+                if ui.checkbox(&mut reverse, "").changed() {
+                    data.reverseStart = Some(reverse);
+                    changed = true;
+                    finished = true;
+                }
+                ui.end_row();
+            });
+
+        if let EEditorTool::PlacePort {
+            port: p,
+            service: s,
+            ..
+        } = &self.editor_data.tool
+        {
+            if p == port && s == service {
+                ui.colored_label(egui::Color32::YELLOW, "Click on the map to place");
+            }
+        }
+
+        (changed, finished)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::views::editor_panel::Route;
+
+    fn editor_with_segment(id: &str, xs: &[f32]) -> EditorData {
+        let mut editor_data = EditorData::default();
+        editor_data.segments.insert(
+            id.to_string(),
+            Segment {
+                id: id.to_string(),
+                route1: Some(xs.iter().map(|x| Pos3::new(*x, 0.0, 0.0)).collect()),
+                selected: false,
+            },
+        );
+        editor_data.routes.push(Route {
+            id: RouteId {
+                start: "a".to_string(),
+                destination: "b".to_string(),
+                service: "Shipmaster".to_string(),
+            },
+            segments: vec![id.to_string()],
+        });
+        editor_data
+    }
+
+    fn xs(editor_data: &EditorData, id: &str) -> Vec<f32> {
+        editor_data.segments[id]
+            .route1
+            .as_ref()
+            .unwrap()
+            .iter()
+            .map(|p| p.x)
+            .collect()
+    }
+
+    #[test]
+    fn split_segment_shares_the_point() {
+        let mut editor_data = editor_with_segment("s", &[0.0, 1.0, 2.0, 3.0]);
+        let mut route = editor_data.routes[0].clone();
+        route.id.service = "Caravaner".to_string();
+        route.segments = vec!["s".to_string(), "t".to_string(), "s".to_string()];
+        editor_data.routes.push(route);
+        editor_data.split_segment("s", 2);
+
+        assert_eq!(xs(&editor_data, "s"), vec![0.0, 1.0, 2.0]);
+        assert_eq!(xs(&editor_data, "s_2"), vec![2.0, 3.0]);
+        assert_eq!(editor_data.routes[0].segments, vec!["s", "s_2"]);
+        // every use of the segment is split
+        assert_eq!(
+            editor_data.routes[1].segments,
+            vec!["s", "s_2", "t", "s", "s_2"]
+        );
+
+        // the end points can't be split
+        editor_data.split_segment("s", 0);
+        editor_data.split_segment("s", 2);
+        assert_eq!(editor_data.segments.len(), 2);
+    }
+
+    #[test]
+    fn join_segments_reverses_and_skips_the_joint() {
+        let mut editor_data = editor_with_segment("s", &[0.0, 100.0]);
+        editor_data.segments.insert(
+            "t".to_string(),
+            Segment {
+                id: "t".to_string(),
+                route1: Some(vec![Pos3::new(300.0, 0.0, 0.0), Pos3::new(100.0, 0.0, 0.0)]),
+                selected: false,
+            },
+        );
+        editor_data.routes[0].segments.push("t".to_string());
+
+        editor_data.join_segments("s", "t");
+        assert_eq!(xs(&editor_data, "s"), vec![0.0, 100.0, 300.0]);
+        assert!(!editor_data.segments.contains_key("t"));
+        assert_eq!(editor_data.routes[0].segments, vec!["s"]);
+
+        editor_data.undo();
+        assert!(editor_data.segments.contains_key("t"));
+        assert_eq!(editor_data.routes[0].segments, vec!["s", "t"]);
+    }
+
+    #[test]
+    fn delete_and_rename_update_routes() {
+        let mut editor_data = editor_with_segment("s", &[0.0, 1.0]);
+        editor_data.ports.insert("a".to_string(), Port::default());
+
+        editor_data.rename_port("a", "harbor");
+        assert!(editor_data.ports.contains_key("harbor"));
+        assert_eq!(editor_data.routes[0].id.start, "harbor");
+
+        let id = editor_data.routes[0].id.clone();
+        let mut new_id = id.clone();
+        new_id.service = "Gondolier".to_string();
+        editor_data.rename_route(&id, new_id.clone());
+        assert_eq!(editor_data.routes[0].id, new_id);
+
+        editor_data.delete_segment("s");
+        assert!(editor_data.segments.is_empty());
+        assert!(editor_data.routes[0].segments.is_empty());
+
+        editor_data.delete_route(&new_id);
+        editor_data.delete_port("harbor");
+        assert!(editor_data.routes.is_empty());
+        assert!(editor_data.ports.is_empty());
+        assert_eq!(editor_data.history.undo.len(), 5);
+    }
+
+    #[test]
+    fn heading_tip_is_clockwise_from_north() {
+        let mut data = PortData::default();
+        assert!(data.heading_tip().distance(Pos2::new(0.0, 512.0)) < 0.01);
+        data.rotation.z = std::f32::consts::FRAC_PI_2;
+        assert!(data.heading_tip().distance(Pos2::new(512.0, 0.0)) < 0.01);
+    }
+}
@@ -0,0 +1,43 @@
+use crate::views::editor_geojson::GeoJsonPaths;
+use crate::views::editor_io::ImmersiveTravel;
+use crate::views::editor_panel::EditorData;
+
+/// Mods whose data can be authored in the editor
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+pub enum EModFormat {
+    #[default]
+    ImmersiveTravel,
+    GeoJson,
+}
+
+impl EModFormat {
+    pub const ALL: [EModFormat; 2] = [EModFormat::ImmersiveTravel, EModFormat::GeoJson];
+
+    pub fn editor(&self) -> &'static dyn ModEditor {
+        match self {
+            EModFormat::ImmersiveTravel => &ImmersiveTravel,
+            EModFormat::GeoJson => &GeoJsonPaths,
+        }
+    }
+}
+
+/// A data format of a mod that is edited as segments of points
+/// Point editing is shared, implementations read and write their own files
+pub trait ModEditor {
+    fn name(&self) -> &'static str;
+
+    /// The mod connects ports with routes made of segments
+    fn has_routes(&self) -> bool {
+        false
+    }
+
+    /// Read the mod folder into the editor, the editor data is cleared before
+    fn load(&self, data: &mut EditorData, errors: &mut Vec<String>);
+
+    /// Write all changed and removed segments
+    fn save_segments(&self, data: &mut EditorData);
+
+    fn save_routes(&self, _data: &mut EditorData) {}
+
+    fn save_ports(&self, _data: &mut EditorData) {}
+}
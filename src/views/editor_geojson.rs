@@ -0,0 +1,103 @@
+use std::io;
+
+use serde::{Deserialize, Serialize};
+
+use crate::views::editor_formats::ModEditor;
+use crate::views::editor_io::read_folder;
+use crate::views::editor_panel::{EditorData, Pos3, Segment};
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+struct PathProperties {
+    #[serde(default)]
+    id: String,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+struct LineString {
+    #[serde(rename = "type")]
+    kind: String,
+    /// [x, y] or [x, y, z] in engine units
+    coordinates: Vec<Vec<f32>>,
+}
+
+/// A GeoJSON feature with a line string in engine coordinates
+#[derive(Debug, Clone, Deserialize, Serialize)]
+struct PathFeature {
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(default)]
+    properties: PathProperties,
+    geometry: LineString,
+}
+
+fn parse_feature(s: &str) -> Result<PathFeature, String> {
+    let feature = serde_json::from_str::<PathFeature>(s).map_err(|e| e.to_string())?;
+    if feature.kind != "Feature" || feature.geometry.kind != "LineString" {
+        return Err("expected a Feature with a LineString geometry".to_owned());
+    }
+    Ok(feature)
+}
+
+fn to_feature(segment: &Segment) -> io::Result<String> {
+    let feature = PathFeature {
+        kind: "Feature".to_owned(),
+        properties: PathProperties {
+            id: segment.id.clone(),
+        },
+        geometry: LineString {
+            kind: "LineString".to_owned(),
+            coordinates: segment
+                .route1
+                .iter()
+                .flatten()
+                .map(|p| vec![p.x, p.y, p.z])
+                .collect(),
+        },
+    };
+    serde_json::to_string_pretty(&feature).map_err(io::Error::other)
+}
+
+/// Generic paths: one GeoJSON line string feature per `.geojson` file in the mod folder
+pub struct GeoJsonPaths;
+
+impl ModEditor for GeoJsonPaths {
+    fn name(&self) -> &'static str {
+        "GeoJSON paths"
+    }
+
+    fn load(&self, data: &mut EditorData, errors: &mut Vec<String>) {
+        for (path, feature) in read_folder(&data.mod_folder, "geojson", parse_feature, errors) {
+            // paths without an id are named after their file
+            let id = if feature.properties.id.is_empty() {
+                path.file_stem()
+                    .and_then(|s| s.to_str())
+                    .unwrap_or_default()
+                    .to_owned()
+            } else {
+                feature.properties.id
+            };
+            let points = feature
+                .geometry
+                .coordinates
+                .iter()
+                .filter(|c| c.len() >= 2)
+                .map(|c| Pos3::new(c[0], c[1], c.get(2).copied().unwrap_or_default()))
+                .collect();
+
+            data.saved.segment_files.insert(id.clone(), path);
+            data.segments.insert(
+                id.clone(),
+                Segment {
+                    id,
+                    route1: Some(points),
+                    selected: false,
+                },
+            );
+        }
+    }
+
+    fn save_segments(&self, data: &mut EditorData) {
+        let folder = data.mod_folder.clone();
+        data.save_segment_files(&folder, "geojson", to_feature);
+    }
+}
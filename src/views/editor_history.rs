@@ -0,0 +1,377 @@
+use egui::ahash::HashMap;
+
+use crate::views::editor_panel::{EditorData, Port, Route, Segment};
+use crate::TemplateApp;
+
+const MAX_HISTORY: usize = 200;
+
+/// A single change of the editor data, None means the item doesn't exist
+#[derive(Debug, Clone)]
+pub enum EEditorChange {
+    Segment {
+        id: String,
+        before: Option<Segment>,
+        after: Option<Segment>,
+    },
+    Port {
+        name: String,
+        before: Option<Port>,
+        after: Option<Port>,
+    },
+    Routes {
+        before: Vec<Route>,
+        after: Vec<Route>,
+    },
+}
+
+/// An undoable edit, the items that differ between `begin_edit` and `commit_edit`
+#[derive(Debug, Clone)]
+pub struct EditorCommand {
+    pub description: String,
+    pub changes: Vec<EEditorChange>,
+}
+
+/// Copy of all editable data taken by `begin_edit`, compared item by item on commit
+#[derive(Debug, Clone, Default)]
+struct EditorSnapshot {
+    segments: HashMap<String, Segment>,
+    ports: HashMap<String, Port>,
+    routes: Vec<Route>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct EditorHistory {
+    pub undo: Vec<EditorCommand>,
+    pub redo: Vec<EditorCommand>,
+    pending: Option<EditorSnapshot>,
+}
+
+impl EditorHistory {
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+        self.pending = None;
+    }
+}
+
+impl EditorData {
+    /// Snapshot the segments, ports and routes, the next `commit_edit` records the difference
+    /// Call it once per user action, not every frame, since it copies all editable data
+    pub fn begin_edit(&mut self) {
+        if self.history.pending.is_some() {
+            return;
+        }
+        self.history.pending = Some(EditorSnapshot {
+            segments: self.segments.clone(),
+            ports: self.ports.clone(),
+            routes: self.routes.clone(),
+        });
+    }
+
+    /// Record the items changed since `begin_edit` as one history entry
+    pub fn commit_edit(&mut self, description: &str) {
+        let Some(snapshot) = self.history.pending.take() else {
+            return;
+        };
+
+        let mut changes = vec![];
+
+        // segments
+        let mut ids = snapshot
+            .segments
+            .keys()
+            .chain(self.segments.keys())
+            .cloned()
+            .collect::<Vec<_>>();
+        ids.sort();
+        ids.dedup();
+        for id in ids {
+            let before = snapshot.segments.get(&id);
+            let after = self.segments.get(&id);
+            if before != after {
+                changes.push(EEditorChange::Segment {
+                    id,
+                    before: before.cloned(),
+                    after: after.cloned(),
+                });
+            }
+        }
+
+        // ports
+        let mut names = snapshot
+            .ports
+            .keys()
+            .chain(self.ports.keys())
+            .cloned()
+            .collect::<Vec<_>>();
+        names.sort();
+        names.dedup();
+        for name in names {
+            let before = snapshot.ports.get(&name);
+            let after = self.ports.get(&name);
+            if before != after {
+                changes.push(EEditorChange::Port {
+                    name,
+                    before: before.cloned(),
+                    after: after.cloned(),
+                });
+            }
+        }
+
+        // routes
+        if snapshot.routes != self.routes {
+            changes.push(EEditorChange::Routes {
+                before: snapshot.routes,
+                after: self.routes.clone(),
+            });
+        }
+
+        if changes.is_empty() {
+            return;
+        }
+
+        self.history.undo.push(EditorCommand {
+            description: description.to_owned(),
+            changes,
+        });
+        self.travel_time_cache = None;
+        if self.history.undo.len() > MAX_HISTORY {
+            self.history.undo.remove(0);
+        }
+        self.history.redo.clear();
+    }
+
+    fn apply_change(&mut self, change: &EEditorChange, forward: bool) {
+        match change {
+            EEditorChange::Segment { id, before, after } => {
+                let target = if forward { after } else { before };
+                let selected = self.segments.get(id).is_some_and(|s| s.selected);
+                match target {
+                    Some(segment) => {
+                        let mut segment = segment.clone();
+                        segment.selected = selected || segment.selected;
+                        self.segments.insert(id.clone(), segment);
+                    }
+                    None => {
+                        self.segments.remove(id);
+                        if self.current_segment.as_ref() == Some(id) {
+                            self.current_segment = None;
+                        }
+                    }
+                }
+            }
+            EEditorChange::Port {
+                name,
+                before,
+                after,
+            } => {
+                let target = if forward { after } else { before };
+                match target {
+                    Some(port) => {
+                        self.ports.insert(name.clone(), port.clone());
+                    }
+                    None => {
+                        self.ports.remove(name);
+                    }
+                }
+            }
+            EEditorChange::Routes { before, after } => {
+                self.routes = if forward { after } else { before }.clone();
+            }
+        }
+    }
+
+    pub fn undo(&mut self) {
+        self.history.pending = None;
+        let Some(command) = self.history.undo.pop() else {
+            return;
+        };
+        for change in command.changes.iter().rev() {
+            self.apply_change(change, false);
+        }
+        self.selected_point = None;
+        self.selected_points.clear();
+        self.history.redo.push(command);
+        self.travel_time_cache = None;
+    }
+
+    pub fn redo(&mut self) {
+        self.history.pending = None;
+        let Some(command) = self.history.redo.pop() else {
+            return;
+        };
+        for change in command.changes.iter() {
+            self.apply_change(change, true);
+        }
+        self.selected_point = None;
+        self.selected_points.clear();
+        self.history.undo.push(command);
+        self.travel_time_cache = None;
+    }
+}
+
+impl TemplateApp {
+    /// Ctrl+Z and Ctrl+Y for the editor
+    pub fn editor_shortcuts(&mut self, ctx: &egui::Context) {
+        if !self.editor_data.enabled || ctx.wants_keyboard_input() {
+            return;
+        }
+
+        let (undo, redo) = ctx.input_mut(|i| {
+            let redo = i.consume_key(
+                egui::Modifiers::COMMAND | egui::Modifiers::SHIFT,
+                egui::Key::Z,
+            ) || i.consume_key(egui::Modifiers::COMMAND, egui::Key::Y);
+            let undo = i.consume_key(egui::Modifiers::COMMAND, egui::Key::Z);
+            (undo, redo)
+        });
+        if undo {
+            self.editor_data.undo();
+        }
+        if redo {
+            self.editor_data.redo();
+        }
+    }
+
+    /// Undo and redo buttons and the list of commands
+    pub fn editor_history_ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            if ui
+                .add_enabled(
+                    !self.editor_data.history.undo.is_empty(),
+                    egui::Button::new("⟲ Undo"),
+                )
+                .on_hover_text("Ctrl+Z")
+                .clicked()
+            {
+                self.editor_data.undo();
+            }
+            if ui
+                .add_enabled(
+                    !self.editor_data.history.redo.is_empty(),
+                    egui::Button::new("⟳ Redo"),
+                )
+                .on_hover_text("Ctrl+Y")
+                .clicked()
+            {
+                self.editor_data.redo();
+            }
+        });
+
+        ui.collapsing("History", |ui| {
+            let history = &self.editor_data.history;
+            if history.undo.is_empty() && history.redo.is_empty() {
+                ui.label("No changes");
+                return;
+            }
+
+            // clicking an entry undoes or redoes up to it
+            let mut undo_count = 0;
+            let mut redo_count = 0;
+            for (i, command) in history.undo.iter().enumerate() {
+                let text = if i + 1 == history.undo.len() {
+                    egui::RichText::new(format!("▶ {}", command.description)).strong()
+                } else {
+                    egui::RichText::new(&command.description)
+                };
+                if ui
+                    .add(egui::Label::new(text).sense(egui::Sense::click()))
+                    .clicked()
+                {
+                    undo_count = history.undo.len() - i - 1;
+                }
+            }
+            for (i, command) in history.redo.iter().rev().enumerate() {
+                let text = egui::RichText::new(&command.description).weak();
+                if ui
+                    .add(egui::Label::new(text).sense(egui::Sense::click()))
+                    .clicked()
+                {
+                    redo_count = i + 1;
+                }
+            }
+
+            for _ in 0..undo_count {
+                self.editor_data.undo();
+            }
+            for _ in 0..redo_count {
+                self.editor_data.redo();
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::views::editor_panel::Pos3;
+
+    fn segment(id: &str, x: f32) -> Segment {
+        Segment {
+            id: id.to_string(),
+            route1: Some(vec![Pos3 { x, y: 0.0, z: 0.0 }]),
+            selected: false,
+        }
+    }
+
+    #[test]
+    fn undo_and_redo_restore_changed_items() {
+        let mut editor_data = EditorData::default();
+        editor_data
+            .segments
+            .insert("a".to_string(), segment("a", 0.0));
+        editor_data
+            .segments
+            .insert("b".to_string(), segment("b", 0.0));
+
+        editor_data.begin_edit();
+        editor_data
+            .segments
+            .insert("a".to_string(), segment("a", 10.0));
+        editor_data.segments.remove("b");
+        editor_data
+            .ports
+            .insert("port".to_string(), Port::default());
+        editor_data.commit_edit("Edit");
+
+        let command = editor_data.history.undo.last().unwrap();
+        assert_eq!(command.changes.len(), 3);
+
+        editor_data.undo();
+        assert_eq!(editor_data.segments["a"], segment("a", 0.0));
+        assert!(editor_data.segments.contains_key("b"));
+        assert!(editor_data.ports.is_empty());
+        assert_eq!(editor_data.history.redo.len(), 1);
+
+        editor_data.redo();
+        assert_eq!(editor_data.segments["a"], segment("a", 10.0));
+        assert!(!editor_data.segments.contains_key("b"));
+        assert!(editor_data.ports.contains_key("port"));
+        assert!(editor_data.history.redo.is_empty());
+    }
+
+    #[test]
+    fn empty_and_selection_only_edits_are_not_recorded() {
+        let mut editor_data = EditorData::default();
+        editor_data
+            .segments
+            .insert("a".to_string(), segment("a", 0.0));
+
+        editor_data.begin_edit();
+        editor_data.segments.get_mut("a").unwrap().selected = true;
+        editor_data.commit_edit("Select");
+        assert!(editor_data.history.undo.is_empty());
+
+        // a new edit clears the redo stack
+        editor_data.begin_edit();
+        editor_data.routes.push(Route::default());
+        editor_data.commit_edit("Add route");
+        editor_data.undo();
+        assert!(editor_data.routes.is_empty());
+        editor_data.begin_edit();
+        editor_data.segments.remove("a");
+        editor_data.commit_edit("Delete");
+        assert!(editor_data.history.redo.is_empty());
+        assert_eq!(editor_data.history.undo.len(), 1);
+    }
+}
@@ -0,0 +1,485 @@
+use std::{
+    fs::{read_dir, read_to_string, remove_file},
+    io,
+    path::{Path, PathBuf},
+};
+
+use egui::ahash::HashMap;
+use serde::Serialize;
+
+use crate::views::editor_formats::ModEditor;
+use crate::views::editor_panel::{EditorData, Port, Route, RouteId, Segment};
+use crate::write_atomic;
+
+/// The editor data as it is on disk, to find unsaved changes
+#[derive(Debug, Clone, Default)]
+pub struct SavedEditorState {
+    pub(crate) segments: HashMap<String, Segment>,
+    pub(crate) ports: HashMap<String, Port>,
+    pub(crate) routes: HashMap<RouteId, Route>,
+    /// files the items were loaded from
+    pub(crate) segment_files: HashMap<String, PathBuf>,
+    pub(crate) route_files: HashMap<RouteId, PathBuf>,
+}
+
+/// Unique file name of a route, routes between the same ports can have different services
+pub fn route_file_name(id: &RouteId) -> String {
+    format!("{}_{}_{}.toml", id.start, id.destination, id.service)
+}
+
+fn to_toml<T: Serialize>(value: &T) -> io::Result<String> {
+    toml::ser::to_string_pretty(value).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+fn write_toml<T: Serialize>(path: &Path, value: &T) -> io::Result<()> {
+    write_atomic(path, to_toml(value)?)
+}
+
+fn parse_toml<T: serde::de::DeserializeOwned>(s: &str) -> Result<T, String> {
+    toml::de::from_str::<T>(s).map_err(|e| e.to_string())
+}
+
+/// Parse all files with an extension in a folder
+pub(crate) fn read_folder<T>(
+    folder: &Path,
+    extension: &str,
+    parse: impl Fn(&str) -> Result<T, String>,
+    errors: &mut Vec<String>,
+) -> Vec<(PathBuf, T)> {
+    let mut items = vec![];
+    let Ok(entries) = read_dir(folder) else {
+        return items;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.extension().is_none_or(|e| e != extension) {
+            continue;
+        }
+        match read_to_string(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|s| parse(&s))
+        {
+            Ok(item) => items.push((path, item)),
+            Err(e) => {
+                log::error!("Failed to parse file '{}': {}", path.display(), e);
+                errors.push(format!("{}: {}", path.display(), e));
+            }
+        }
+    }
+    items
+}
+
+/// Remove a file that is no longer used, logging failures
+pub(crate) fn remove_old_file(path: &Path, errors: &mut Vec<String>) {
+    if path.exists() {
+        if let Err(e) = remove_file(path) {
+            log::error!("Failed to remove '{}': {}", path.display(), e);
+            errors.push(format!("{}: {}", path.display(), e));
+        }
+    }
+}
+
+impl EditorData {
+    /// Load the mod folder with the selected format
+    pub fn load(&mut self) {
+        self.current_segment = None;
+        self.picked_point = None;
+        self.selected_points.clear();
+        self.history.clear();
+        self.travel_time_cache = None;
+        self.routes_metadata.clear();
+        self.playback = None;
+        self.io_errors.clear();
+        self.routes.clear();
+        self.segments.clear();
+        self.ports.clear();
+        self.saved = SavedEditorState::default();
+
+        let mut errors = vec![];
+        self.format.editor().load(self, &mut errors);
+
+        self.saved.segments = self.segments.clone();
+        self.saved.ports = self.ports.clone();
+        self.saved.routes = self
+            .routes
+            .iter()
+            .map(|r| (r.id.clone(), r.clone()))
+            .collect();
+        self.io_errors = errors;
+    }
+
+    // unsaved changes
+    pub fn dirty_segments(&self) -> Vec<String> {
+        let mut ids = self
+            .segments
+            .iter()
+            .filter(|(id, s)| self.saved.segments.get(*id) != Some(*s))
+            .map(|(id, _)| id.clone())
+            .collect::<Vec<_>>();
+        ids.sort();
+        ids
+    }
+
+    pub fn dirty_ports(&self) -> Vec<String> {
+        let mut names = self
+            .ports
+            .iter()
+            .filter(|(name, p)| self.saved.ports.get(*name) != Some(*p))
+            .map(|(name, _)| name.clone())
+            .collect::<Vec<_>>();
+        names.sort();
+        names
+    }
+
+    pub fn dirty_routes(&self) -> Vec<RouteId> {
+        let mut ids = self
+            .routes
+            .iter()
+            .filter(|r| self.saved.routes.get(&r.id) != Some(*r))
+            .map(|r| r.id.clone())
+            .collect::<Vec<_>>();
+        ids.sort_by(|a, b| {
+            (&a.start, &a.destination, &a.service).cmp(&(&b.start, &b.destination, &b.service))
+        });
+        ids
+    }
+
+    fn removed_items(&self) -> bool {
+        self.saved
+            .segments
+            .keys()
+            .any(|k| !self.segments.contains_key(k))
+            || self.saved.ports.keys().any(|k| !self.ports.contains_key(k))
+            || self
+                .saved
+                .routes
+                .keys()
+                .any(|k| !self.routes.iter().any(|r| r.id == *k))
+    }
+
+    pub fn is_dirty(&self) -> bool {
+        !self.dirty_segments().is_empty()
+            || !self.dirty_ports().is_empty()
+            || !self.dirty_routes().is_empty()
+            || self.removed_items()
+    }
+
+    // saving, each save replaces the errors of the last one
+    pub fn save_segments(&mut self) {
+        self.io_errors.clear();
+        self.format.editor().save_segments(self);
+    }
+
+    pub fn save_ports(&mut self) {
+        self.io_errors.clear();
+        self.format.editor().save_ports(self);
+    }
+
+    pub fn save_routes(&mut self) {
+        self.io_errors.clear();
+        self.format.editor().save_routes(self);
+    }
+
+    pub fn save_all(&mut self) {
+        self.io_errors.clear();
+        let editor = self.format.editor();
+        editor.save_segments(self);
+        editor.save_ports(self);
+        editor.save_routes(self);
+    }
+
+    /// Write changed segments to one file each and remove the files of removed segments
+    pub(crate) fn save_segment_files(
+        &mut self,
+        folder: &Path,
+        extension: &str,
+        serialize: impl Fn(&Segment) -> io::Result<String>,
+    ) {
+        let mut errors = vec![];
+
+        for id in self.dirty_segments() {
+            let Some(segment) = self.segments.get(&id) else {
+                continue;
+            };
+
+            // round route1 points to 0 decimal places
+            let mut segment = segment.clone();
+            if let Some(ref mut route1) = segment.route1 {
+                for point in route1.iter_mut() {
+                    point.x = (point.x).round();
+                    point.y = (point.y).round();
+                    point.z = (point.z).round();
+                }
+            }
+
+            let path = folder.join(format!("{}.{}", segment.id, extension));
+            match serialize(&segment).and_then(|contents| write_atomic(&path, contents)) {
+                Ok(_) => {
+                    self.saved.segments.insert(id.clone(), segment.clone());
+                    self.saved.segment_files.insert(id.clone(), path);
+                    // keep the in-memory segment in sync with the file
+                    self.segments.insert(id, segment);
+                }
+                Err(e) => errors.push(format!("{}: {}", path.display(), e)),
+            }
+        }
+
+        // removed and renamed segments
+        let removed = self
+            .saved
+            .segments
+            .keys()
+            .filter(|id| !self.segments.contains_key(*id))
+            .cloned()
+            .collect::<Vec<_>>();
+        for id in removed {
+            let path = self
+                .saved
+                .segment_files
+                .remove(&id)
+                .unwrap_or(folder.join(format!("{}.{}", id, extension)));
+            remove_old_file(&path, &mut errors);
+            self.saved.segments.remove(&id);
+        }
+
+        self.report_save("segments", errors);
+    }
+
+    pub(crate) fn report_save(&mut self, what: &str, errors: Vec<String>) {
+        if errors.is_empty() {
+            log::info!("Saved {}", what);
+        } else {
+            for error in errors.iter() {
+                log::error!("Failed to save {}: {}", what, error);
+            }
+            self.io_errors.extend(errors);
+        }
+    }
+}
+
+/// Immersive Travel: toml files in `routes/`, `segments/` and `ports/`
+pub struct ImmersiveTravel;
+
+impl ModEditor for ImmersiveTravel {
+    fn name(&self) -> &'static str {
+        "Immersive Travel"
+    }
+
+    fn has_routes(&self) -> bool {
+        true
+    }
+
+    fn load(&self, data: &mut EditorData, errors: &mut Vec<String>) {
+        // routes
+        for (path, route) in read_folder::<Route>(&data.routes_folder(), "toml", parse_toml, errors)
+        {
+            data.saved.route_files.insert(route.id.clone(), path);
+            data.routes.push(route);
+        }
+
+        // segments
+        for (path, segment) in
+            read_folder::<Segment>(&data.segments_folder(), "toml", parse_toml, errors)
+        {
+            data.saved.segment_files.insert(segment.id.clone(), path);
+            data.segments.insert(segment.id.clone(), segment);
+        }
+
+        // ports are named after their file
+        for (path, port) in read_folder::<Port>(&data.ports_folder(), "toml", parse_toml, errors) {
+            if let Some(name) = path.file_stem().and_then(|s| s.to_str()) {
+                data.ports.insert(name.to_owned(), port);
+            }
+        }
+    }
+
+    fn save_segments(&self, data: &mut EditorData) {
+        data.save_segment_files(&data.segments_folder(), "toml", to_toml);
+    }
+
+    fn save_routes(&self, data: &mut EditorData) {
+        let mut errors = vec![];
+        let folder = data.routes_folder();
+
+        for route in data.routes.clone() {
+            let path = folder.join(route_file_name(&route.id));
+            // the old file may use another name
+            let old_path = data.saved.route_files.get(&route.id).cloned();
+            if data.saved.routes.get(&route.id) == Some(&route) && old_path.as_ref() == Some(&path)
+            {
+                continue;
+            }
+
+            match write_toml(&path, &route) {
+                Ok(_) => {
+                    if let Some(old_path) = old_path.filter(|p| *p != path) {
+                        remove_old_file(&old_path, &mut errors);
+                    }
+                    data.saved.routes.insert(route.id.clone(), route.clone());
+                    data.saved.route_files.insert(route.id.clone(), path);
+                }
+                Err(e) => errors.push(format!("{}: {}", path.display(), e)),
+            }
+        }
+
+        // removed routes
+        let removed = data
+            .saved
+            .routes
+            .keys()
+            .filter(|id| !data.routes.iter().any(|r| r.id == **id))
+            .cloned()
+            .collect::<Vec<_>>();
+        for id in removed {
+            if let Some(path) = data.saved.route_files.remove(&id) {
+                remove_old_file(&path, &mut errors);
+            }
+            data.saved.routes.remove(&id);
+        }
+
+        data.report_save("routes", errors);
+    }
+
+    fn save_ports(&self, data: &mut EditorData) {
+        let mut errors = vec![];
+        let folder = data.ports_folder();
+
+        for name in data.dirty_ports() {
+            let Some(port) = data.ports.get(&name) else {
+                continue;
+            };
+            let path = folder.join(format!("{}.toml", name));
+            match write_toml(&path, port) {
+                Ok(_) => {
+                    data.saved.ports.insert(name, port.clone());
+                }
+                Err(e) => errors.push(format!("{}: {}", path.display(), e)),
+            }
+        }
+
+        // removed ports
+        let removed = data
+            .saved
+            .ports
+            .keys()
+            .filter(|name| !data.ports.contains_key(*name))
+            .cloned()
+            .collect::<Vec<_>>();
+        for name in removed {
+            remove_old_file(&folder.join(format!("{}.toml", name)), &mut errors);
+            data.saved.ports.remove(&name);
+        }
+
+        data.report_save("ports", errors);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+    use crate::views::editor_panel::Pos3;
+
+    fn segment(id: &str, x: f32) -> Segment {
+        Segment {
+            id: id.to_string(),
+            route1: Some(vec![Pos3::new(x, 0.0, 0.0)]),
+            selected: false,
+        }
+    }
+
+    fn route(start: &str, destination: &str) -> Route {
+        Route {
+            id: RouteId {
+                start: start.to_string(),
+                destination: destination.to_string(),
+                service: "Shipmaster".to_string(),
+            },
+            segments: vec![],
+        }
+    }
+
+    fn temp_folder(name: &str) -> PathBuf {
+        let folder = std::env::temp_dir().join(format!("tes3map_{}_{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&folder);
+        for sub in ["routes", "segments", "ports"] {
+            fs::create_dir_all(folder.join(sub)).unwrap();
+        }
+        folder
+    }
+
+    #[test]
+    fn dirty_tracking_ignores_selection() {
+        let mut editor_data = EditorData::default();
+        editor_data
+            .segments
+            .insert("a".to_string(), segment("a", 0.0));
+        editor_data.saved.segments = editor_data.segments.clone();
+        assert!(!editor_data.is_dirty());
+
+        editor_data.segments.get_mut("a").unwrap().selected = true;
+        assert!(!editor_data.is_dirty());
+
+        // a changed id with the same points still needs saving
+        editor_data.segments.get_mut("a").unwrap().id = "b".to_string();
+        assert_eq!(editor_data.dirty_segments(), vec!["a"]);
+
+        editor_data.routes = vec![route("c", "d"), route("a", "b")];
+        let dirty = editor_data.dirty_routes();
+        assert_eq!(dirty[0].start, "a");
+        assert_eq!(dirty[1].start, "c");
+    }
+
+    #[test]
+    fn save_and_load_round_trip() {
+        let folder = temp_folder("round_trip");
+        let mut editor_data = EditorData {
+            mod_folder: folder.clone(),
+            ..Default::default()
+        };
+        editor_data
+            .segments
+            .insert("a".to_string(), segment("a", 1.4));
+        editor_data
+            .ports
+            .insert("harbor".to_string(), Port::default());
+        editor_data.routes.push(route("harbor", "harbor"));
+        editor_data.io_errors.push("old error".to_string());
+
+        editor_data.save_all();
+        assert!(editor_data.io_errors.is_empty());
+        assert!(!editor_data.is_dirty());
+        // points are rounded when saved
+        assert_eq!(editor_data.segments["a"].route1.as_ref().unwrap()[0].x, 1.0);
+
+        editor_data.load();
+        assert_eq!(editor_data.segments.len(), 1);
+        assert_eq!(editor_data.ports.len(), 1);
+        assert_eq!(editor_data.routes.len(), 1);
+        assert!(!editor_data.is_dirty());
+
+        // removed items delete their files
+        editor_data.segments.clear();
+        assert!(editor_data.is_dirty());
+        editor_data.save_segments();
+        assert!(!folder.join("segments").join("a.toml").exists());
+
+        let _ = fs::remove_dir_all(&folder);
+    }
+
+    #[test]
+    fn write_atomic_replaces_the_file() {
+        let folder = temp_folder("write_atomic");
+        let path = folder.join("file.toml");
+        write_atomic(&path, "first").unwrap();
+        write_atomic(&path, "second").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "second");
+        assert!(!folder.join("file.toml.tmp").exists());
+
+        // a missing folder fails without leaving a file behind
+        assert!(write_atomic(&folder.join("missing").join("file.toml"), "x").is_err());
+
+        let _ = fs::remove_dir_all(&folder);
+    }
+}
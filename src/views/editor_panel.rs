@@ -1,16 +1,28 @@
-use std::{
-    fs::{read_dir, read_to_string},
-    path::PathBuf,
-};
+use std::path::PathBuf;
 
-use crate::fs::write;
 use egui::{ahash::HashMap, emath::RectTransform, Context, Pos2, Response};
 
-use crate::TemplateApp;
+use crate::views::editor_authoring::EEditorTool;
+use crate::views::editor_formats::EModFormat;
+use crate::views::editor_history::EditorHistory;
+use crate::views::editor_io::SavedEditorState;
+use crate::views::editor_playback::{Playback, PlaybackSettings};
+use crate::views::editor_selection::PointRef;
+use crate::views::editor_splines::SplineSettings;
+use crate::views::editor_terrain::TerrainSettings;
+use crate::views::editor_validation::RouteError;
+use crate::views::measure::format_duration;
+use crate::{pathfinding::WaterPathSettings, TemplateApp};
 
 #[derive(Debug, Clone, Default)]
 pub struct RouteMetadata {
-    pub valid: bool,
+    pub errors: Vec<RouteError>,
+}
+
+impl RouteMetadata {
+    pub fn is_valid(&self) -> bool {
+        self.errors.is_empty()
+    }
 }
 
 /// This is the editor panel for the map editor
@@ -18,6 +30,9 @@ pub struct RouteMetadata {
 pub struct EditorData {
     pub enabled: bool,
     pub mod_folder: PathBuf,
+    /// data format of the mod folder
+    #[serde(default)]
+    pub format: EModFormat,
 
     // display options
     #[serde(skip)]
@@ -31,11 +46,87 @@ pub struct EditorData {
     #[serde(skip)]
     pub ports: HashMap<String, Port>,
 
-    /// (segment id, point index)
+    /// point that is being dragged
+    #[serde(skip)]
+    pub selected_point: Option<PointRef>,
+    /// port and service whose rotation handle is being dragged
     #[serde(skip)]
-    pub selected_point: Option<(String, usize)>,
+    pub rotating_port: Option<(String, String)>,
     #[serde(skip)]
     pub current_segment: Option<String>,
+
+    /// undo and redo stacks
+    #[serde(skip)]
+    pub history: EditorHistory,
+    /// travel times for the playback settings, None after the routes or segments changed
+    #[serde(skip)]
+    pub travel_time_cache: Option<(PlaybackSettings, HashMap<RouteId, f32>)>,
+
+    // authoring
+    #[serde(skip)]
+    pub tool: EEditorTool,
+    /// last clicked point of the current segment
+    #[serde(skip)]
+    pub picked_point: Option<PointRef>,
+    /// points selected by clicking or with the selection box
+    #[serde(skip)]
+    pub selected_points: Vec<PointRef>,
+    /// point under the mouse
+    #[serde(skip)]
+    pub hovered_point: Option<PointRef>,
+    /// selection box corners in canvas coordinates
+    #[serde(skip)]
+    pub box_select: Option<(Pos2, Pos2)>,
+    /// engine position where a move of the selected points started
+    #[serde(skip)]
+    pub drag_start: Option<Pos2>,
+    /// positions of the moved points before the move
+    #[serde(skip)]
+    pub drag_origins: Vec<(PointRef, Pos3)>,
+    #[serde(skip)]
+    pub new_name: String,
+    #[serde(skip)]
+    pub join_target: String,
+    #[serde(skip)]
+    pub route_draft: Route,
+    #[serde(skip)]
+    pub new_port_name: String,
+    #[serde(skip)]
+    pub new_port_service: String,
+    #[serde(skip)]
+    pub new_route_service: String,
+
+    // spline tools
+    #[serde(default)]
+    pub spline_settings: SplineSettings,
+    /// result of the spline tool on a segment before it is applied
+    #[serde(skip)]
+    pub spline_preview: Option<(String, Vec<Pos3>)>,
+
+    // heights
+    #[serde(default)]
+    pub terrain_settings: TerrainSettings,
+
+    // water pathfinder
+    #[serde(default)]
+    pub pathfinder_settings: WaterPathSettings,
+    /// start and end in engine coordinates
+    #[serde(skip)]
+    pub path_endpoints: [Option<Pos2>; 2],
+    #[serde(skip)]
+    pub path_error: Option<String>,
+
+    // route playback
+    #[serde(default)]
+    pub playback_settings: PlaybackSettings,
+    #[serde(skip)]
+    pub playback: Option<Playback>,
+
+    // saving
+    #[serde(skip)]
+    pub saved: SavedEditorState,
+    #[serde(skip)]
+    pub io_errors: Vec<String>,
 }
 
 impl EditorData {
@@ -54,7 +145,7 @@ impl EditorData {
 }
 
 // route struct
-#[derive(Debug, Clone, Default, serde::Deserialize, serde::Serialize)]
+#[derive(Debug, Clone, Default, serde::Deserialize, serde::Serialize, PartialEq)]
 pub struct Route {
     pub id: RouteId,
     pub segments: Vec<String>,
@@ -90,14 +181,21 @@ pub struct Segment {
     pub selected: bool,
 }
 
+// the selection is not part of the saved data
+impl PartialEq for Segment {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id && self.route1 == other.route1
+    }
+}
+
 // port struct
-#[derive(Debug, Clone, Default, serde::Deserialize, serde::Serialize)]
+#[derive(Debug, Clone, Default, serde::Deserialize, serde::Serialize, PartialEq)]
 pub struct Port {
     pub data: HashMap<String, PortData>,
 }
 
 #[allow(non_snake_case)]
-#[derive(Debug, Clone, Default, serde::Deserialize, serde::Serialize)]
+#[derive(Debug, Clone, Default, serde::Deserialize, serde::Serialize, PartialEq)]
 pub struct PortData {
     pub position: Pos3,
     pub rotation: Pos3,
@@ -108,7 +206,7 @@ pub struct PortData {
     pub reverseStart: Option<bool>,
 }
 
-#[derive(Debug, Clone, Default, serde::Deserialize, serde::Serialize)]
+#[derive(Debug, Clone, Default, serde::Deserialize, serde::Serialize, PartialEq)]
 pub struct Pos3 {
     pub x: f32,
     pub y: f32,
@@ -140,6 +238,21 @@ impl TemplateApp {
         // The editor panel should have a list of routes, loaded from a folder
         // Each route is serialized in a toml file
 
+        // mod format
+        let mut format = self.editor_data.format;
+        egui::ComboBox::from_label("Format")
+            .selected_text(format.editor().name())
+            .show_ui(ui, |ui| {
+                for f in EModFormat::ALL {
+                    ui.selectable_value(&mut format, f, f.editor().name());
+                }
+            });
+        if format != self.editor_data.format && self.confirm_discard_changes() {
+            self.editor_data.format = format;
+            self.editor_data.load();
+        }
+        let has_routes = self.editor_data.format.editor().has_routes();
+
         // folder control
         ui.horizontal(|ui| {
             if ui.button("Open mod folder").clicked() {
@@ -161,165 +274,79 @@ impl TemplateApp {
 
         ui.separator();
 
-        // load button
-        if ui
-            .button(
-                egui::RichText::new("Load routes")
-                    .color(egui::Color32::DARK_GREEN)
-                    .strong(),
-            )
-            .clicked()
-        {
-            self.editor_data.current_segment = None;
-
-            // load routes from folder
-            self.editor_data.routes.clear();
-            if let Ok(entries) = read_dir(self.editor_data.routes_folder()) {
-                for entry in entries.flatten() {
-                    if let Ok(file) = read_to_string(entry.path()) {
-                        // parse the file as a route
-                        if let Ok(route) = toml::de::from_str::<Route>(&file) {
-                            self.editor_data.routes.push(route);
-                        } else {
-                            log::error!("Failed to parse route file: {}", entry.path().display());
-                        }
-                    }
-                }
-            }
-
-            // validate button
-            if ui
-                .button(egui::RichText::new("Validate routes").color(egui::Color32::ORANGE))
-                .clicked()
-            {
-                // validate routes
-                self.editor_data.routes_metadata.clear();
-                // go through all routes and check if the segments in the route connect
-                for route in &self.editor_data.routes {
-                    let mut metadata = RouteMetadata { valid: true };
-
-                    // check if all segments exist and put into a flat array
-                    let mut segment_vec = Vec::new();
-                    for segment in &route.segments {
-                        if let Some(segment) = self.editor_data.segments.get(segment) {
-                            segment_vec.push(segment);
-                        }
-                    }
-
-                    // validate that all segments are there
-                    if segment_vec.len() == route.segments.len() {
-                        log::info!("Route '{}' is valid", route.id);
-                    } else {
-                        log::warn!("Route '{}' is invalid", route.id);
-                        metadata.valid = false;
-                    }
+        // load and validate buttons
+        let (load_clicked, validate_clicked) = ui
+            .horizontal(|ui| {
+                let load = ui
+                    .button(
+                        egui::RichText::new("Load")
+                            .color(egui::Color32::DARK_GREEN)
+                            .strong(),
+                    )
+                    .clicked();
+                let validate = has_routes
+                    && ui
+                        .button(egui::RichText::new("Validate routes").color(egui::Color32::ORANGE))
+                        .clicked();
+                (load, validate)
+            })
+            .inner;
+
+        if validate_clicked {
+            self.validate_routes();
+        }
 
-                    // validate that all segments connect
-                    // start with the port
+        if load_clicked && self.confirm_discard_changes() {
+            self.editor_data.load();
+        }
 
-                    self.editor_data
-                        .routes_metadata
-                        .insert(route.id.clone(), metadata);
-                }
+        // save buttons
+        let dirty_segments = self.editor_data.dirty_segments().len();
+        let dirty_ports = self.editor_data.dirty_ports().len();
+        let dirty_routes = self.editor_data.dirty_routes().len();
+        ui.horizontal(|ui| {
+            if has_routes
+                && ui
+                    .button(format!("Save routes ({})", dirty_routes))
+                    .clicked()
+            {
+                self.editor_data.save_routes();
             }
-
-            // load segments from folder
-            self.editor_data.segments.clear();
-            if let Ok(entries) = read_dir(self.editor_data.segments_folder()) {
-                for entry in entries.flatten() {
-                    if let Ok(file) = read_to_string(entry.path()) {
-                        // parse the file as a segment
-                        if let Ok(segment) = toml::de::from_str::<Segment>(&file) {
-                            self.editor_data
-                                .segments
-                                .insert(segment.id.clone(), segment);
-                        } else {
-                            log::error!("Failed to parse segment file: {}", entry.path().display());
-                        }
-                    }
-                }
+            if has_routes && ui.button(format!("Save ports ({})", dirty_ports)).clicked() {
+                self.editor_data.save_ports();
             }
-
-            // load ports from folder
-            self.editor_data.ports.clear();
-            if let Ok(entries) = read_dir(self.editor_data.ports_folder()) {
-                for entry in entries.flatten() {
-                    if let Ok(file) = read_to_string(entry.path()) {
-                        // parse the file as a port
-                        match toml::de::from_str::<Port>(&file) {
-                            Ok(port) => {
-                                let name = entry
-                                    .file_name()
-                                    .to_str()
-                                    .unwrap()
-                                    .replace(".toml", "")
-                                    .to_string();
-                                self.editor_data.ports.insert(name, port);
-                            }
-                            Err(e) => {
-                                log::error!(
-                                    "Failed to parse segment file '{}': {}",
-                                    entry.path().display(),
-                                    e
-                                );
-                            }
-                        }
-                    }
-                }
+            if ui
+                .button(format!("Save segments ({})", dirty_segments))
+                .on_hover_text("Saves all modified segments")
+                .clicked()
+            {
+                self.editor_data.save_segments();
             }
+        });
+        if ui
+            .add_enabled(self.editor_data.is_dirty(), egui::Button::new("Save all"))
+            .clicked()
+        {
+            self.editor_data.save_all();
         }
 
-        ui.horizontal(|ui| {
-            // save button
+        // load and save errors
+        if !self.editor_data.io_errors.is_empty() {
             ui.horizontal(|ui| {
-                if ui.button("Save routes").clicked() {
-                    // save routes to folder
-                    for route in &self.editor_data.routes {
-                        let file = toml::ser::to_string_pretty(route).unwrap();
-                        let path = self
-                            .editor_data
-                            .routes_folder()
-                            .join(format!("{}_{}.toml", route.id.start, route.id.destination));
-                        write(path, file).unwrap();
-                    }
-                }
-
-                if ui.button("Save ports").clicked() {
-                    // save routes to folder
-                    for (name, port) in &self.editor_data.ports {
-                        let file = toml::ser::to_string_pretty(port).unwrap();
-                        let path = self
-                            .editor_data
-                            .ports_folder()
-                            .join(format!("{}.toml", name));
-                        write(path, file).unwrap();
-                    }
+                ui.colored_label(egui::Color32::RED, "Errors:");
+                if ui.small_button("x").clicked() {
+                    self.editor_data.io_errors.clear();
                 }
+            });
+            for error in &self.editor_data.io_errors {
+                ui.colored_label(egui::Color32::RED, error);
+            }
+        }
 
-                if ui.button("Save segments").clicked() {
-                    // save segments to folder
-                    for (_, segment) in self.editor_data.segments.iter().filter(|(_, s)| s.selected)
-                    {
-                        // round route1 points to 0 decimal places
-                        let mut segment = segment.clone();
-                        if let Some(ref mut route1) = segment.route1 {
-                            for point in route1.iter_mut() {
-                                point.x = (point.x).round();
-                                point.y = (point.y).round();
-                                point.z = (point.z).round();
-                            }
-                        }
+        ui.separator();
 
-                        let file = toml::ser::to_string_pretty(&segment).unwrap();
-                        let path = self
-                            .editor_data
-                            .segments_folder()
-                            .join(format!("{}.toml", segment.id));
-                        write(path, file).unwrap();
-                    }
-                }
-            });
-        });
+        // undo and redo
+        self.editor_history_ui(ui);
 
         ui.separator();
 
@@ -328,68 +355,105 @@ impl TemplateApp {
 
         ui.separator();
 
+        let mut focus = None;
+        let mut delete_route = None;
+        let mut rename_route = None;
         egui::ScrollArea::vertical().show(ui, |ui| {
             // Routes list
-            ui.collapsing("Routes", |ui| {
-                for route in &self.editor_data.routes {
-                    ui.horizontal(|ui| {
-                        // check if routes is invalid
-                        if let Some(metadata) = self.editor_data.routes_metadata.get(&route.id) {
-                            if !metadata.valid {
-                                ui.label("✖");
+            if has_routes {
+                let travel_times = self.editor_data.route_travel_times();
+                ui.collapsing("Routes", |ui| {
+                    for route in &self.editor_data.routes {
+                        ui.horizontal(|ui| {
+                            // check if routes is invalid
+                            if let Some(metadata) = self.editor_data.routes_metadata.get(&route.id) {
+                                if !metadata.is_valid() {
+                                    ui.label("✖");
+                                } else {
+                                    ui.label("✅");
+                                }
                             } else {
-                                ui.label("✅");
+                                ui.label("❓");
                             }
-                        } else {
-                            ui.label("❓");
-                        }
 
-                        // Determine if exactly this route is active (all its segments selected, none others)
-                        let route_seg_set: std::collections::HashSet<&String> =
-                            route.segments.iter().collect();
+                            // Determine if exactly this route is active (all its segments selected, none others)
+                            let route_seg_set: std::collections::HashSet<&String> =
+                                route.segments.iter().collect();
 
-                        let all_in_route_selected = route.segments.iter().all(
-                            |id| matches!(self.editor_data.segments.get(id), Some(s) if s.selected),
-                        );
-                        let any_outside_selected = self
-                            .editor_data
-                            .segments
-                            .iter()
-                            .any(|(id, s)| !route_seg_set.contains(id) && s.selected);
+                            let all_in_route_selected = route.segments.iter().all(
+                                |id| matches!(self.editor_data.segments.get(id), Some(s) if s.selected),
+                            );
+                            let any_outside_selected = self
+                                .editor_data
+                                .segments
+                                .iter()
+                                .any(|(id, s)| !route_seg_set.contains(id) && s.selected);
+
+                            let mut checked = all_in_route_selected && !any_outside_selected;
+                            if ui.checkbox(&mut checked, "").changed() {
+                                // Exclusive selection: if checked, enable only this route's segments; if unchecked, disable all
+                                if checked {
+                                    self.editor_data.current_segment = None;
+                                    for (_id, seg) in self.editor_data.segments.iter_mut() {
+                                        seg.selected = false;
+                                    }
+                                    for seg_id in &route.segments {
+                                        if let Some(seg) = self.editor_data.segments.get_mut(seg_id) {
+                                            seg.selected = true;
+                                        }
+                                    }
+                                } else {
+                                    for (_id, seg) in self.editor_data.segments.iter_mut() {
+                                        seg.selected = false;
+                                    }
+                                    self.editor_data.current_segment = None;
+                                }
+                            }
 
-                        let mut checked = all_in_route_selected && !any_outside_selected;
-                        if ui.checkbox(&mut checked, "").changed() {
-                            // Exclusive selection: if checked, enable only this route's segments; if unchecked, disable all
-                            if checked {
-                                self.editor_data.current_segment = None;
-                                for (_id, seg) in self.editor_data.segments.iter_mut() {
-                                    seg.selected = false;
+                            let name = format!(
+                                "{} -> {} ({}) ~{}",
+                                route.id.start,
+                                route.id.destination,
+                                route.id.service,
+                                format_duration(
+                                    travel_times.get(&route.id).copied().unwrap_or_default()
+                                )
+                            );
+                            ui.collapsing(name, |ui| {
+                                for segment in &route.segments {
+                                    ui.label(segment);
                                 }
-                                for seg_id in &route.segments {
-                                    if let Some(seg) = self.editor_data.segments.get_mut(seg_id) {
-                                        seg.selected = true;
+
+                                ui.horizontal(|ui| {
+                                    ui.text_edit_singleline(&mut self.editor_data.new_route_service);
+                                    if ui.button("Rename service").clicked() {
+                                        let mut id = route.id.clone();
+                                        id.service =
+                                            self.editor_data.new_route_service.trim().to_owned();
+                                        rename_route = Some((route.id.clone(), id));
                                     }
+                                });
+                                if ui.button("Delete route").clicked() {
+                                    delete_route = Some(route.id.clone());
                                 }
-                            } else {
-                                for (_id, seg) in self.editor_data.segments.iter_mut() {
-                                    seg.selected = false;
-                                }
-                                self.editor_data.current_segment = None;
-                            }
-                        }
 
-                        let name = format!(
-                            "{} -> {} ({})",
-                            route.id.start, route.id.destination, route.id.service
-                        );
-                        ui.collapsing(name, |ui| {
-                            for segment in &route.segments {
-                                ui.label(segment);
-                            }
+                                // validation errors, click to show on the map
+                                if let Some(metadata) = self.editor_data.routes_metadata.get(&route.id)
+                                {
+                                    for error in &metadata.errors {
+                                        let text = egui::RichText::new(&error.message)
+                                            .color(egui::Color32::RED);
+                                        let label = egui::Label::new(text).sense(egui::Sense::click());
+                                        if ui.add(label).clicked() {
+                                            focus = error.position;
+                                        }
+                                    }
+                                }
+                            });
                         });
-                    });
-                }
-            });
+                    }
+                });
+            }
 
             // Segments list
             ui.collapsing("Segments", |ui| {
@@ -439,21 +503,69 @@ impl TemplateApp {
                 }
             });
 
-            // Ports list
-            ui.collapsing("Ports", |ui| {
-                let mut keys: Vec<String> = self.editor_data.ports.keys().cloned().collect();
-                keys.sort();
+            // Segment tools
+            ui.collapsing("Segment tools", |ui| {
+                self.editor_segment_tools_ui(ui);
+            });
 
-                for id in keys {
-                    //let port = self.editor_data.ports.get(&id).unwrap();
+            // Spline tools
+            let spline_tools = ui.collapsing("Spline tools", |ui| {
+                self.editor_spline_tools_ui(ui);
+            });
+            if spline_tools.body_returned.is_none() {
+                // only preview while the tools are open
+                self.editor_data.spline_preview = None;
+            }
 
-                    ui.horizontal(|ui| {
-                        ui.label(id.clone());
-                        //ui.label(format!("{:?}", port.data.position));
-                    });
-                }
+            // Heights
+            ui.collapsing("Heights", |ui| {
+                self.editor_terrain_ui(ui);
+            });
+
+            // Water pathfinder
+            ui.collapsing("Water path", |ui| {
+                self.editor_pathfinder_ui(ui);
             });
+
+            if has_routes {
+                // Route builder
+                ui.collapsing("New route", |ui| {
+                    self.editor_route_builder_ui(ui);
+                });
+
+                // Ports list
+                ui.collapsing("Ports", |ui| {
+                    self.editor_ports_ui(ui);
+                });
+
+                // Route playback
+                ui.collapsing("Playback", |ui| {
+                    self.editor_playback_ui(ui);
+                });
+            }
         });
+
+        if let Some(position) = focus {
+            self.focus_engine(position);
+        }
+        if let Some(id) = delete_route {
+            self.editor_data.delete_route(&id);
+        }
+        if let Some((id, new_id)) = rename_route {
+            self.editor_data.rename_route(&id, new_id);
+            self.editor_data.new_route_service.clear();
+        }
+    }
+
+    /// Ask before unsaved changes are thrown away
+    fn confirm_discard_changes(&self) -> bool {
+        !self.editor_data.is_dirty()
+            || rfd::MessageDialog::new()
+                .set_title("Unsaved changes")
+                .set_description("Reloading discards the unsaved changes. Continue?")
+                .set_buttons(rfd::MessageButtons::YesNo)
+                .show()
+                == rfd::MessageDialogResult::Yes
     }
 
     // events
@@ -463,93 +575,99 @@ impl TemplateApp {
             return;
         }
 
-        if let Some((selected_point, i)) = &self.editor_data.selected_point {
-            // get the segment
-            if let Some(segment) = self.editor_data.segments.get_mut(selected_point) {
-                // get the route
-                if let Some(route1) = &mut segment.route1 {
-                    // get the point
-                    if let Some(_point) = route1.get_mut(*i) {
-                        // translate the point to screen space
-                        let clicked_point = from_screen * current_pos;
-                        let engine_pos = self
-                            .dimensions
-                            .canvas_to_engine(Pos2::new(clicked_point.x, clicked_point.y));
-
-                        // snap to port points but don't change the port
-                        let mut snap_point = None;
-                        for port in self.editor_data.ports.values() {
-                            if snap_point.is_some() {
-                                break;
-                            }
+        // translate the point to screen space
+        let clicked_point = from_screen * current_pos;
+        let engine_pos = self
+            .dimensions
+            .canvas_to_engine(Pos2::new(clicked_point.x, clicked_point.y));
 
-                            for data in port.data.values() {
-                                // start positions
-                                {
-                                    let port_pos = Pos2::new(data.position.x, data.position.y);
-                                    let dist = (engine_pos - port_pos).length();
-                                    if dist < 600.0 {
-                                        // do not change the port position, just snap the point to it
-                                        snap_point = Some(port_pos);
-                                        break;
-                                    }
-                                }
+        if self.editor_data.rotating_port.is_some() {
+            self.editor_rotate_port(engine_pos);
+            return;
+        }
 
-                                // reverse start positions
-                                {
-                                    if let Some(position) = &data.positionStart {
-                                        let port_pos = Pos2::new(position.x, position.y);
-                                        let dist = (engine_pos - port_pos).length();
-                                        if dist < 600.0 {
-                                            // do not change the port position, just snap the point to it
-                                            snap_point = Some(port_pos);
-                                            break;
-                                        }
-                                    }
-                                }
-                            }
-                        }
+        let Some((selected_point, i)) = self.editor_data.selected_point.clone() else {
+            return;
+        };
 
-                        // snap the point
-                        if let Some(snap_point) = snap_point {
-                            _point.x = snap_point.x;
-                            _point.y = snap_point.y;
-                        } else {
-                            _point.x = engine_pos.x;
-                            _point.y = engine_pos.y;
-                        }
+        // move the whole selection without snapping
+        if let Some(drag_start) = self.editor_data.drag_start {
+            self.move_selected_points(engine_pos - drag_start);
+            return;
+        }
 
-                        // check if the point is close to another point in a different segment
-                        // and snap to it
-                        for (id, segment) in self.editor_data.segments.iter_mut() {
-                            // continue if segment is not selected
-                            if !segment.selected {
-                                continue;
-                            }
+        // snap to port points but don't change the port
+        let mut snap_point = None;
+        for port in self.editor_data.ports.values() {
+            if snap_point.is_some() {
+                break;
+            }
 
-                            if id != selected_point {
-                                if let Some(route1) = &mut segment.route1 {
-                                    for point in route1.iter_mut() {
-                                        let dist =
-                                            (engine_pos - Pos2::new(point.x, point.y)).length();
-                                        if dist < 600.0 {
-                                            // set the point to the new position
-                                            if let Some(snap_point) = snap_point {
-                                                point.x = snap_point.x;
-                                                point.y = snap_point.y;
-                                            } else {
-                                                point.x = engine_pos.x;
-                                                point.y = engine_pos.y;
-                                            }
-                                        }
-                                    }
-                                }
-                            }
+            for data in port.data.values() {
+                // start positions
+                {
+                    let port_pos = Pos2::new(data.position.x, data.position.y);
+                    let dist = (engine_pos - port_pos).length();
+                    if dist < 600.0 {
+                        // do not change the port position, just snap the point to it
+                        snap_point = Some(port_pos);
+                        break;
+                    }
+                }
+
+                // reverse start positions
+                {
+                    if let Some(position) = &data.positionStart {
+                        let port_pos = Pos2::new(position.x, position.y);
+                        let dist = (engine_pos - port_pos).length();
+                        if dist < 600.0 {
+                            // do not change the port position, just snap the point to it
+                            snap_point = Some(port_pos);
+                            break;
                         }
                     }
                 }
             }
         }
+
+        // snap the point
+        let target = snap_point.unwrap_or(engine_pos);
+        let z = self.editor_point_z(&selected_point, target);
+
+        // get the point
+        let Some(point) = self
+            .editor_data
+            .segments
+            .get_mut(&selected_point)
+            .and_then(|s| s.route1.as_mut())
+            .and_then(|r| r.get_mut(i))
+        else {
+            return;
+        };
+        point.x = target.x;
+        point.y = target.y;
+        point.z = z;
+
+        // check if the point is close to another point in a different segment
+        // and snap to it
+        for (id, segment) in self.editor_data.segments.iter_mut() {
+            // continue if segment is not selected
+            if !segment.selected || *id == selected_point {
+                continue;
+            }
+
+            if let Some(route1) = &mut segment.route1 {
+                for point in route1.iter_mut() {
+                    let dist = (engine_pos - Pos2::new(point.x, point.y)).length();
+                    if dist < 600.0 {
+                        // set the point to the new position
+                        point.x = target.x;
+                        point.y = target.y;
+                        point.z = z;
+                    }
+                }
+            }
+        }
     }
 
     pub fn editor_on_ctrl_right_clicked(&mut self, from_screen: RectTransform, interact_pos: Pos2) {
@@ -559,6 +677,7 @@ impl TemplateApp {
 
         let found_point = self.get_point(from_screen, interact_pos, false);
         if let Some((id, i)) = found_point {
+            self.editor_data.begin_edit();
             // get the segment
             if let Some(segment) = self.editor_data.segments.get_mut(&id) {
                 // get the route
@@ -567,6 +686,9 @@ impl TemplateApp {
                     route1.remove(i);
                 }
             }
+            self.editor_data.commit_edit("Delete point");
+            self.editor_data.selected_points.clear();
+            self.editor_data.picked_point = None;
         }
     }
 
@@ -575,37 +697,33 @@ impl TemplateApp {
             return;
         }
 
-        // if found, remove the point from the segment
-
         // add a point to the selected segment
-        if let Some(selected_segment) = &self.editor_data.current_segment {
+        self.editor_data.begin_edit();
+        if let Some(selected_segment) = self.editor_data.current_segment.clone() {
+            let points = self
+                .editor_data
+                .segments
+                .get(&selected_segment)
+                .and_then(|s| s.route1.clone())
+                .unwrap_or_default();
+            // insert on the closest edge
+            let (index, engine_pos) = self.get_insert_position(from_screen, &points, interact_pos);
+            let z = self.editor_point_z(&selected_segment, engine_pos);
+
             // get the segment
-            if let Some(segment) = self.editor_data.segments.get_mut(selected_segment) {
-                // get the route
-                if let Some(route1) = &mut segment.route1 {
-                    // get point coordinates
-                    let clicked_point = from_screen * interact_pos;
-                    let engine_pos = self.dimensions.canvas_to_engine(clicked_point);
-
-                    // add the point after the nearest point
-                    let mut min_dist = f32::MAX;
-                    let mut min_index = 0;
-                    for (i, point) in route1.iter().enumerate() {
-                        let dist = (engine_pos - Pos2::new(point.x, point.y)).length();
-                        if dist < min_dist {
-                            min_dist = dist;
-                            min_index = i;
-                        }
-                    }
-                    // add the point to the route
-                    if min_index == route1.len() - 1 {
-                        route1.push(Pos3::new(engine_pos.x, engine_pos.y, 0.0));
-                    } else {
-                        route1.insert(min_index, Pos3::new(engine_pos.x, engine_pos.y, 0.0));
-                    }
-                }
+            if let Some(route1) = self
+                .editor_data
+                .segments
+                .get_mut(&selected_segment)
+                .and_then(|s| s.route1.as_mut())
+            {
+                route1.insert(index, Pos3::new(engine_pos.x, engine_pos.y, z));
             }
         }
+        self.editor_data.commit_edit("Add point");
+        // indices after the new point changed
+        self.editor_data.selected_points.clear();
+        self.editor_data.picked_point = None;
     }
 
     pub fn editor_on_ctrl_drag_started(&mut self, from_screen: RectTransform, interact_pos: Pos2) {
@@ -614,9 +732,55 @@ impl TemplateApp {
             return;
         }
 
+        // port headings are turned with the handle at their tip
+        if let Some(handle) = self.editor_port_handle_at(from_screen, interact_pos) {
+            self.editor_data.rotating_port = Some(handle);
+            self.editor_data.begin_edit();
+            return;
+        }
+
         let found_point = self.get_point(from_screen, interact_pos, false);
-        if let Some((id, i)) = found_point {
-            self.editor_data.selected_point = Some((id.clone(), i));
+        if let Some(point) = found_point {
+            // dragging a selected point moves the whole selection
+            if self.editor_data.selected_points.len() > 1
+                && self.editor_data.selected_points.contains(&point)
+            {
+                self.editor_data.drag_start =
+                    Some(self.dimensions.canvas_to_engine(from_screen * interact_pos));
+                self.editor_data.drag_origins = self
+                    .editor_data
+                    .selected_points
+                    .iter()
+                    .filter_map(|(id, i)| {
+                        let point = self
+                            .editor_data
+                            .segments
+                            .get(id)?
+                            .route1
+                            .as_ref()?
+                            .get(*i)?;
+                        Some(((id.clone(), *i), point.clone()))
+                    })
+                    .collect();
+            }
+            self.editor_data.selected_point = Some(point);
+            // the move is recorded when the drag stops
+            self.editor_data.begin_edit();
+        }
+    }
+
+    pub fn editor_on_drag_stopped(&mut self) {
+        if self.editor_data.rotating_port.take().is_some() {
+            self.editor_data.commit_edit("Rotate port");
+        }
+        if self.editor_data.selected_point.take().is_some() {
+            let description = if self.editor_data.drag_start.take().is_some() {
+                "Move points"
+            } else {
+                "Move point"
+            };
+            self.editor_data.drag_origins.clear();
+            self.editor_data.commit_edit(description);
         }
     }
 
@@ -630,15 +794,22 @@ impl TemplateApp {
             return false;
         }
 
+        if self.editor_tool_on_click(from_screen, interact_pos) {
+            return true;
+        }
+
         let found_point = self.get_point(from_screen, interact_pos, true);
 
-        // if found, remove the point from the segment
-        if let Some((id, _i)) = found_point {
+        // if found, select the segment and remember the point
+        if let Some((id, i)) = found_point {
             // set the current segment
             self.editor_data.current_segment = Some(id.clone());
+            self.editor_data.selected_points = vec![(id.clone(), i)];
+            self.editor_data.picked_point = Some((id, i));
             return true;
         }
 
+        self.editor_data.selected_points.clear();
         false
     }
 
@@ -653,31 +824,40 @@ impl TemplateApp {
             return false;
         }
 
-        // context menu for editor mode
         // get point
         let found_point = self.get_point(from_screen, pointer_pos, true);
+        self.editor_data.hovered_point = found_point.clone();
         if let Some((id, i)) = found_point {
             ui.set_width(200.0);
 
-            if let Some(segment) = self.editor_data.segments.get_mut(&id) {
-                // get the route
-                if let Some(route1) = &mut segment.route1 {
-                    if self.ui_data.show_tooltips && ui.ui_contains_pointer() {
-                        response.clone().on_hover_ui_at_pointer(|ui| {
-                            ui.set_width(200.0);
-
-                            // labels
-                            ui.label(format!("Segment: {}", id));
-                            ui.label(format!("Point: {}", route1[i]));
+            // get the route
+            let point = self
+                .editor_data
+                .segments
+                .get(&id)
+                .and_then(|s| s.route1.as_ref())
+                .and_then(|r| r.get(i));
+            if let Some(point) = point {
+                if self.ui_data.show_tooltips && ui.ui_contains_pointer() {
+                    let selected = self.editor_data.selected_points.len();
+                    response.clone().on_hover_ui_at_pointer(|ui| {
+                        ui.set_width(200.0);
+
+                        // labels
+                        ui.label(format!("Segment: {}", id));
+                        ui.label(format!("Point {}: {} z {:.0}", i, point, point.z));
+                        if selected > 1 {
+                            ui.label(format!("{} points selected", selected));
+                        }
 
-                            ui.separator();
+                        ui.separator();
 
-                            ui.label("Ctrl + Click to remove point".to_string());
-                            ui.label("Ctrl + Drag to move point".to_string());
-                            ui.label("Click to select segment".to_string());
-                            ui.label("Ctrl + Click to add point".to_string());
-                        });
-                    }
+                        ui.label("Click to select segment".to_string());
+                        ui.label("Ctrl + Click to add point".to_string());
+                        ui.label("Ctrl + Right click to remove point".to_string());
+                        ui.label("Ctrl + Drag to move point or selection".to_string());
+                        ui.label("Shift + Drag to select points".to_string());
+                    });
                 }
             }
 
@@ -686,49 +866,4 @@ impl TemplateApp {
 
         false
     }
-
-    // methods
-
-    fn get_point(
-        &mut self,
-        from_screen: RectTransform,
-        interact_pos: Pos2,
-        ignore_current_segment: bool,
-    ) -> Option<(String, usize)> {
-        // try to get a point from the segments if distace is less than 10 pixels
-        let mut found = false;
-        let mut found_point = None;
-
-        // check if within distance of any point in displayed segments
-        for (id, segment) in self.editor_data.segments.iter().filter(|(_, s)| s.selected) {
-            // check if the segment is selected for editing
-            if !ignore_current_segment {
-                if let Some(selected_segment) = &self.editor_data.current_segment {
-                    if selected_segment != id {
-                        continue;
-                    }
-                }
-            }
-
-            if let Some(route1) = &segment.route1 {
-                for (i, point) in route1.iter().enumerate() {
-                    let clicked_point = from_screen * interact_pos;
-                    let canvas_pos = self
-                        .dimensions
-                        .engine_to_canvas(Pos2::new(point.x, point.y));
-
-                    let dist = (clicked_point - canvas_pos).length();
-                    if dist < 0.1 {
-                        found = true;
-                        found_point = Some((id.clone(), i));
-                        break;
-                    }
-                }
-            }
-            if found {
-                break;
-            }
-        }
-        found_point
-    }
 }
@@ -0,0 +1,123 @@
+use egui::Pos2;
+
+use crate::pathfinding::find_water_path;
+use crate::views::editor_authoring::EEditorTool;
+use crate::views::editor_panel::{Pos3, Segment};
+use crate::TemplateApp;
+
+const ENDPOINT_NAMES: [&str; 2] = ["Start", "End"];
+
+impl TemplateApp {
+    /// Find a water path between the two endpoints and add it as a new segment
+    fn editor_find_water_path(&mut self) {
+        let [Some(start), Some(end)] = self.editor_data.path_endpoints else {
+            return;
+        };
+
+        let path = match find_water_path(
+            &self.heights,
+            &self.dimensions,
+            start,
+            end,
+            &self.editor_data.pathfinder_settings,
+        ) {
+            Ok(path) => path,
+            Err(e) => {
+                log::error!("{}", e);
+                self.editor_data.path_error = Some(e);
+                return;
+            }
+        };
+        self.editor_data.path_error = None;
+
+        let name = self.editor_data.new_name.trim();
+        let name = if name.is_empty() { "water_path" } else { name };
+        let id = self.editor_data.unique_segment_id(name);
+        let points = path
+            .iter()
+            .map(|p| Pos3::new(p.x, p.y, self.editor_point_z(&id, *p)))
+            .collect();
+        self.editor_data.begin_edit();
+        self.editor_data.segments.insert(
+            id.clone(),
+            Segment {
+                id: id.clone(),
+                route1: Some(points),
+                selected: true,
+            },
+        );
+        self.editor_data.commit_edit("Find water path");
+        self.editor_data.current_segment = Some(id);
+        self.editor_data.picked_point = None;
+    }
+
+    /// Pathfinder endpoints and settings
+    pub fn editor_pathfinder_ui(&mut self, ui: &mut egui::Ui) {
+        let mut port_positions = self
+            .editor_data
+            .ports
+            .iter()
+            .flat_map(|(name, port)| {
+                port.data.iter().map(move |(service, data)| {
+                    (
+                        format!("{} ({})", name, service),
+                        Pos2::new(data.position.x, data.position.y),
+                    )
+                })
+            })
+            .collect::<Vec<_>>();
+        port_positions.sort_by(|a, b| a.0.cmp(&b.0));
+
+        for (i, endpoint_name) in ENDPOINT_NAMES.iter().enumerate() {
+            ui.horizontal(|ui| {
+                ui.label(*endpoint_name);
+                match self.editor_data.path_endpoints[i] {
+                    Some(p) => ui.label(format!("({:.0}, {:.0})", p.x, p.y)),
+                    None => ui.label("-"),
+                };
+
+                let mut picking = self.editor_data.tool == EEditorTool::PickPathPoint(i);
+                if ui
+                    .toggle_value(&mut picking, "📌")
+                    .on_hover_text("Click on the map to place the endpoint")
+                    .changed()
+                {
+                    self.editor_data.tool = if picking {
+                        EEditorTool::PickPathPoint(i)
+                    } else {
+                        EEditorTool::Select
+                    };
+                }
+
+                egui::ComboBox::from_id_salt(("path_port", i))
+                    .selected_text("Port")
+                    .show_ui(ui, |ui| {
+                        for (label, position) in &port_positions {
+                            if ui.selectable_label(false, label).clicked() {
+                                self.editor_data.path_endpoints[i] = Some(*position);
+                            }
+                        }
+                    });
+            });
+        }
+
+        let settings = &mut self.editor_data.pathfinder_settings;
+        ui.add(egui::Slider::new(&mut settings.min_depth, 0.0..=2048.0).text("Minimum depth"));
+        ui.add(egui::Slider::new(&mut settings.clearance, 0.0..=4096.0).text("Shore clearance"));
+        ui.add(egui::Slider::new(&mut settings.resolution, 1..=16).text("Resolution"))
+            .on_hover_text("Height samples per step, larger is faster but coarser");
+        ui.add(egui::Slider::new(&mut settings.margin, 0..=32).text("Search margin (cells)"));
+
+        let ready = self.editor_data.path_endpoints.iter().all(Option::is_some);
+        if ui
+            .add_enabled(ready, egui::Button::new("Find water path"))
+            .on_hover_text("Adds the path as a new segment, named like the name in Segment tools")
+            .clicked()
+        {
+            self.editor_find_water_path();
+        }
+        if let Some(error) = &self.editor_data.path_error {
+            ui.colored_label(egui::Color32::RED, error);
+        }
+    }
+}
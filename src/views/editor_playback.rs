@@ -0,0 +1,485 @@
+use std::f32::consts::PI;
+
+use egui::{ahash::HashMap, Pos2};
+
+use crate::views::editor_panel::{EditorData, Route, RouteId};
+use crate::views::measure::format_duration;
+use crate::TemplateApp;
+
+/// Simulation step in seconds
+const TIME_STEP: f32 = 0.1;
+
+#[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct PlaybackSettings {
+    /// units per second for services without their own speed
+    pub default_speed: f32,
+    pub service_speeds: HashMap<String, f32>,
+    /// maximum turn rate in degrees per second
+    pub turn_rate: f32,
+    /// game time per real time
+    pub timescale: f32,
+}
+
+impl Default for PlaybackSettings {
+    fn default() -> Self {
+        Self {
+            default_speed: 150.0,
+            service_speeds: HashMap::default(),
+            turn_rate: 30.0,
+            timescale: 30.0,
+        }
+    }
+}
+
+impl PlaybackSettings {
+    pub fn speed(&self, service: &str) -> f32 {
+        self.service_speeds
+            .get(service)
+            .copied()
+            .unwrap_or(self.default_speed)
+            .max(1.0)
+    }
+
+    /// Simulated track of a vehicle of the service along the points
+    pub fn simulate(&self, points: &[Pos2], service: &str) -> Vec<TrackSample> {
+        simulate_route(points, self.speed(service), self.turn_rate.max(1.0))
+    }
+
+    /// In-game seconds for real seconds
+    pub fn game_time(&self, seconds: f32) -> f32 {
+        seconds * self.timescale
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct TrackSample {
+    pub position: Pos2,
+    /// radians clockwise from north
+    pub heading: f32,
+}
+
+/// A simulated vehicle on a route
+#[derive(Debug, Clone)]
+pub struct Playback {
+    pub route: RouteId,
+    /// samples every `TIME_STEP` seconds
+    pub track: Vec<TrackSample>,
+    /// corners that are too sharp for the turn rate
+    pub sharp_turns: Vec<Pos2>,
+    pub time: f32,
+    pub playing: bool,
+    /// playback speed multiplier
+    pub rate: f32,
+}
+
+impl Playback {
+    pub fn duration(&self) -> f32 {
+        track_duration(&self.track)
+    }
+
+    pub fn current(&self) -> Option<TrackSample> {
+        let i = ((self.time / TIME_STEP) as usize).min(self.track.len().checked_sub(1)?);
+        let (a, b) = (
+            self.track[i],
+            *self.track.get(i + 1).unwrap_or(&self.track[i]),
+        );
+        let t = (self.time / TIME_STEP - i as f32).clamp(0.0, 1.0);
+        Some(TrackSample {
+            position: a.position.lerp(b.position, t),
+            heading: a.heading + wrap_angle(b.heading - a.heading) * t,
+        })
+    }
+}
+
+fn heading_of(v: egui::Vec2) -> f32 {
+    v.x.atan2(v.y)
+}
+
+fn wrap_angle(a: f32) -> f32 {
+    (a + PI).rem_euclid(2.0 * PI) - PI
+}
+
+fn track_duration(track: &[TrackSample]) -> f32 {
+    track.len().saturating_sub(1) as f32 * TIME_STEP
+}
+
+impl EditorData {
+    /// Estimated in-game travel times of all routes from the simulated playback
+    /// The routes are simulated again only after an edit, undo, redo, load or settings change
+    pub fn route_travel_times(&mut self) -> HashMap<RouteId, f32> {
+        let settings = &self.playback_settings;
+        if self
+            .travel_time_cache
+            .as_ref()
+            .is_none_or(|(cached, _)| cached != settings)
+        {
+            let times = self
+                .routes
+                .iter()
+                .map(|route| {
+                    let track = settings.simulate(&route_points(self, route), &route.id.service);
+                    (route.id.clone(), settings.game_time(track_duration(&track)))
+                })
+                .collect();
+            self.travel_time_cache = Some((settings.clone(), times));
+        }
+
+        self.travel_time_cache
+            .as_ref()
+            .map(|(_, times)| times.clone())
+            .unwrap_or_default()
+    }
+}
+
+/// All points of the route segments in order, shared joints only once
+pub fn route_points(editor_data: &EditorData, route: &Route) -> Vec<Pos2> {
+    let mut points: Vec<Pos2> = vec![];
+    for id in &route.segments {
+        for p in editor_data
+            .segments
+            .get(id)
+            .and_then(|s| s.route1.as_ref())
+            .into_iter()
+            .flatten()
+        {
+            let p = Pos2::new(p.x, p.y);
+            if points.last().is_none_or(|last| last.distance(p) > 1.0) {
+                points.push(p);
+            }
+        }
+    }
+    points
+}
+
+/// Corners where a vehicle with the turning radius can't follow the path
+/// The turn needs `radius * tan(angle / 2)` on both edges
+pub fn find_sharp_turns(points: &[Pos2], radius: f32) -> Vec<Pos2> {
+    points
+        .windows(3)
+        .filter(|w| {
+            let (a, b) = (w[1] - w[0], w[2] - w[1]);
+            let angle = wrap_angle(heading_of(b) - heading_of(a)).abs();
+            let needed = radius * (angle / 2.0).tan();
+            // backtracking is always wrong
+            angle > 0.9 * PI || needed > 0.5 * a.length().min(b.length())
+        })
+        .map(|w| w[1])
+        .collect()
+}
+
+/// Drive along the points with a fixed speed and a limited turn rate
+pub fn simulate_route(points: &[Pos2], speed: f32, turn_rate: f32) -> Vec<TrackSample> {
+    let (Some(first), Some(second)) = (points.first(), points.get(1)) else {
+        return vec![];
+    };
+
+    let mut position = *first;
+    let mut heading = heading_of(*second - *first);
+    let mut track = vec![TrackSample { position, heading }];
+    let max_turn = turn_rate.to_radians() * TIME_STEP;
+    let length = points.windows(2).map(|w| w[0].distance(w[1])).sum::<f32>();
+    // generous limit in case the vehicle circles around a point
+    let max_steps = (10.0 * length / speed / TIME_STEP) as usize + 1000;
+
+    let mut target = 1;
+    while target < points.len() && track.len() < max_steps {
+        let (a, b) = (points[target - 1], points[target]);
+        let to_target = b - position;
+        // reached or passed the point
+        if to_target.length() < speed * TIME_STEP || (b - a).dot(position - b) >= 0.0 {
+            target += 1;
+            continue;
+        }
+
+        let turn = wrap_angle(heading_of(to_target) - heading);
+        heading = wrap_angle(heading + turn.clamp(-max_turn, max_turn));
+        position += egui::vec2(heading.sin(), heading.cos()) * speed * TIME_STEP;
+        track.push(TrackSample { position, heading });
+    }
+
+    track
+}
+
+impl TemplateApp {
+    pub fn editor_start_playback(&mut self, route_id: &RouteId) {
+        let Some(route) = self
+            .editor_data
+            .routes
+            .iter()
+            .find(|r| r.id == *route_id)
+            .cloned()
+        else {
+            return;
+        };
+
+        // show only the segments of the route
+        self.editor_data.current_segment = None;
+        for (id, segment) in self.editor_data.segments.iter_mut() {
+            segment.selected = route.segments.contains(id);
+        }
+
+        let settings = &self.editor_data.playback_settings;
+        let speed = settings.speed(&route.id.service);
+        let points = route_points(&self.editor_data, &route);
+        let radius = speed / settings.turn_rate.max(1.0).to_radians();
+        self.editor_data.playback = Some(Playback {
+            route: route.id.clone(),
+            track: settings.simulate(&points, &route.id.service),
+            sharp_turns: find_sharp_turns(&points, radius),
+            time: 0.0,
+            playing: true,
+            rate: 1.0,
+        });
+    }
+
+    /// Advance a running playback, called every frame
+    pub fn editor_update_playback(&mut self, ctx: &egui::Context) {
+        let Some(playback) = &mut self.editor_data.playback else {
+            return;
+        };
+        if !playback.playing {
+            return;
+        }
+
+        let dt = ctx.input(|i| i.stable_dt).min(0.1);
+        playback.time += dt * playback.rate;
+        if playback.time >= playback.duration() {
+            playback.time = playback.duration();
+            playback.playing = false;
+        }
+        ctx.request_repaint();
+    }
+
+    /// Route selection, playback controls and vehicle settings
+    pub fn editor_playback_ui(&mut self, ui: &mut egui::Ui) {
+        let mut route_ids = self
+            .editor_data
+            .routes
+            .iter()
+            .map(|r| r.id.clone())
+            .collect::<Vec<_>>();
+        route_ids.sort_by_key(|id| id.to_string());
+
+        let selected = self.editor_data.playback.as_ref().map(|p| p.route.clone());
+        let mut start = None;
+        egui::ComboBox::from_id_salt("playback_route")
+            .selected_text(
+                selected
+                    .as_ref()
+                    .map(|id| id.to_string())
+                    .unwrap_or("Route".to_owned()),
+            )
+            .show_ui(ui, |ui| {
+                for id in &route_ids {
+                    if ui
+                        .selectable_label(selected.as_ref() == Some(id), id.to_string())
+                        .clicked()
+                    {
+                        start = Some(id.clone());
+                    }
+                }
+            });
+        if let Some(id) = start {
+            self.editor_start_playback(&id);
+        }
+
+        // settings
+        let settings = &mut self.editor_data.playback_settings;
+        ui.horizontal(|ui| {
+            ui.add(
+                egui::DragValue::new(&mut settings.default_speed)
+                    .range(1.0..=2000.0)
+                    .suffix(" u/s"),
+            );
+            ui.label("Default speed");
+        });
+        let mut services = route_ids
+            .iter()
+            .map(|id| id.service.clone())
+            .collect::<Vec<_>>();
+        services.sort();
+        services.dedup();
+        for service in services {
+            ui.horizontal(|ui| {
+                let mut speed = settings.speed(&service);
+                if ui
+                    .add(
+                        egui::DragValue::new(&mut speed)
+                            .range(1.0..=2000.0)
+                            .suffix(" u/s"),
+                    )
+                    .changed()
+                {
+                    settings.service_speeds.insert(service.clone(), speed);
+                }
+                ui.label(&service);
+            });
+        }
+        ui.add(egui::Slider::new(&mut settings.turn_rate, 1.0..=180.0).text("Turn rate (°/s)"));
+        ui.add(egui::Slider::new(&mut settings.timescale, 1.0..=100.0).text("Timescale"));
+        let game_time = settings.game_time(1.0);
+
+        let Some(route) = selected else {
+            return;
+        };
+
+        ui.separator();
+
+        let mut restart = false;
+        let mut stop = false;
+        let mut focus = None;
+        if let Some(playback) = &mut self.editor_data.playback {
+            ui.horizontal(|ui| {
+                let label = if playback.playing { "⏸" } else { "▶" };
+                if ui.button(label).clicked() {
+                    if playback.time >= playback.duration() {
+                        playback.time = 0.0;
+                    }
+                    playback.playing = !playback.playing;
+                }
+                restart = ui
+                    .button("⟲")
+                    .on_hover_text("Restart with the current settings")
+                    .clicked();
+                stop = ui.button("⏹").clicked();
+                ui.add(
+                    egui::DragValue::new(&mut playback.rate)
+                        .range(0.1..=100.0)
+                        .speed(0.1)
+                        .suffix("x"),
+                );
+            });
+            let duration = playback.duration();
+            ui.add(egui::Slider::new(&mut playback.time, 0.0..=duration).show_value(false));
+            ui.label(format!(
+                "{} / {}",
+                format_duration(playback.time),
+                format_duration(duration)
+            ));
+            ui.label(format!(
+                "Travel time: ~{} game time",
+                format_duration(duration * game_time)
+            ));
+
+            if !playback.sharp_turns.is_empty() {
+                ui.colored_label(
+                    egui::Color32::ORANGE,
+                    format!("{} sharp turns", playback.sharp_turns.len()),
+                );
+                for (i, position) in playback.sharp_turns.iter().enumerate() {
+                    let text = format!("Turn {} at ({:.0}, {:.0})", i + 1, position.x, position.y);
+                    if ui
+                        .add(egui::Label::new(text).sense(egui::Sense::click()))
+                        .clicked()
+                    {
+                        focus = Some(*position);
+                    }
+                }
+            }
+        }
+
+        if restart {
+            self.editor_start_playback(&route);
+        }
+        if stop {
+            self.editor_data.playback = None;
+        }
+        if let Some(position) = focus {
+            self.focus_engine(position);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::views::editor_panel::{Pos3, Segment};
+    use egui::pos2;
+
+    #[test]
+    fn straight_route_takes_length_by_speed() {
+        let track = simulate_route(&[pos2(0.0, 0.0), pos2(300.0, 0.0)], 100.0, 30.0);
+        assert!((track_duration(&track) - 3.0).abs() <= TIME_STEP);
+        let end = track.last().unwrap().position;
+        assert!(end.distance(pos2(300.0, 0.0)) < 10.0);
+    }
+
+    #[test]
+    fn turn_rate_lengthens_corners() {
+        let points = [pos2(0.0, 0.0), pos2(1000.0, 0.0), pos2(1000.0, 1000.0)];
+        let fast = track_duration(&simulate_route(&points, 100.0, 180.0));
+        let slow = track_duration(&simulate_route(&points, 100.0, 10.0));
+        assert!((fast - 20.0).abs() <= 2.0 * TIME_STEP);
+        assert!(slow > fast + 1.0);
+    }
+
+    #[test]
+    fn reports_backtracking_and_sharp_corners() {
+        let corner = [pos2(0.0, 0.0), pos2(1000.0, 0.0), pos2(1000.0, 1000.0)];
+        assert_eq!(find_sharp_turns(&corner, 100.0), vec![]);
+        assert_eq!(find_sharp_turns(&corner, 2000.0), vec![pos2(1000.0, 0.0)]);
+
+        let straight = [pos2(0.0, 0.0), pos2(500.0, 0.0), pos2(1000.0, 0.0)];
+        assert_eq!(find_sharp_turns(&straight, 2000.0), vec![]);
+
+        // even a tiny radius can't turn around
+        let back = [pos2(0.0, 0.0), pos2(1000.0, 0.0), pos2(0.0, 10.0)];
+        assert_eq!(find_sharp_turns(&back, 1.0), vec![pos2(1000.0, 0.0)]);
+    }
+
+    #[test]
+    fn max_steps_end_impossible_routes() {
+        // turning around at 1°/s takes longer than the limit
+        let points = [pos2(0.0, 0.0), pos2(100.0, 0.0), pos2(0.0, 0.0)];
+        let track = simulate_route(&points, 100.0, 1.0);
+        let max_steps = (10.0 * 200.0 / 100.0 / TIME_STEP) as usize + 1000;
+        assert_eq!(track.len(), max_steps);
+        assert!(track.last().unwrap().position.distance(points[2]) > 100.0);
+    }
+
+    #[test]
+    fn travel_times_match_the_playback() {
+        let mut editor_data = EditorData::default();
+        editor_data.segments.insert(
+            "s".to_string(),
+            Segment {
+                id: "s".to_string(),
+                route1: Some(vec![Pos3::new(0.0, 0.0, 0.0), Pos3::new(300.0, 0.0, 0.0)]),
+                selected: false,
+            },
+        );
+        let id = RouteId {
+            start: "a".to_string(),
+            destination: "b".to_string(),
+            service: "Shipmaster".to_string(),
+        };
+        editor_data.routes.push(Route {
+            id: id.clone(),
+            segments: vec!["s".to_string()],
+        });
+        editor_data.playback_settings.default_speed = 100.0;
+        editor_data.playback_settings.timescale = 10.0;
+        let time = editor_data.route_travel_times()[&id];
+        assert!((time - 30.0).abs() <= 10.0 * TIME_STEP);
+
+        // settings changes apply
+        editor_data
+            .playback_settings
+            .service_speeds
+            .insert("Shipmaster".to_string(), 300.0);
+        let time = editor_data.route_travel_times()[&id];
+        assert!((time - 10.0).abs() <= 10.0 * TIME_STEP);
+
+        editor_data.begin_edit();
+        let route1 = editor_data.segments.get_mut("s").unwrap().route1.as_mut();
+        route1.unwrap()[1].x = 900.0;
+        editor_data.commit_edit("Move point");
+        let time = editor_data.route_travel_times()[&id];
+        assert!((time - 30.0).abs() <= 10.0 * TIME_STEP);
+
+        editor_data.undo();
+        let time = editor_data.route_travel_times()[&id];
+        assert!((time - 10.0).abs() <= 10.0 * TIME_STEP);
+    }
+}
@@ -0,0 +1,162 @@
+use egui::{emath::RectTransform, Pos2, Rect};
+
+use crate::views::editor_panel::Pos3;
+use crate::{distance_to_segment, TemplateApp};
+
+/// Picking distance in screen pixels
+pub const PICK_RADIUS: f32 = 8.0;
+
+/// A point of a segment: (segment id, point index)
+pub type PointRef = (String, usize);
+
+impl TemplateApp {
+    /// The closest point within the pick radius
+    /// Only points of the current segment unless `ignore_current_segment`
+    pub(crate) fn get_point(
+        &self,
+        from_screen: RectTransform,
+        interact_pos: Pos2,
+        ignore_current_segment: bool,
+    ) -> Option<PointRef> {
+        let to_screen = from_screen.inverse();
+        let mut found_point = None;
+        let mut min_dist = PICK_RADIUS;
+
+        // check if within distance of any point in displayed segments
+        for (id, segment) in self.editor_data.segments.iter().filter(|(_, s)| s.selected) {
+            // check if the segment is selected for editing
+            if !ignore_current_segment
+                && self
+                    .editor_data
+                    .current_segment
+                    .as_ref()
+                    .is_some_and(|current| current != id)
+            {
+                continue;
+            }
+
+            for (i, point) in segment.route1.iter().flatten().enumerate() {
+                let screen_pos = to_screen
+                    * self
+                        .dimensions
+                        .engine_to_canvas(Pos2::new(point.x, point.y));
+                let dist = (interact_pos - screen_pos).length();
+                if dist < min_dist {
+                    min_dist = dist;
+                    found_point = Some((id.clone(), i));
+                }
+            }
+        }
+        found_point
+    }
+
+    /// Index to insert a clicked point into a segment and the inserted position
+    /// Clicks near an edge are projected onto it, other clicks extend the nearer end
+    pub(crate) fn get_insert_position(
+        &self,
+        from_screen: RectTransform,
+        points: &[Pos3],
+        interact_pos: Pos2,
+    ) -> (usize, Pos2) {
+        let to_screen = from_screen.inverse();
+        let engine_pos = self.dimensions.canvas_to_engine(from_screen * interact_pos);
+        let screen_points = points
+            .iter()
+            .map(|p| to_screen * self.dimensions.engine_to_canvas(Pos2::new(p.x, p.y)))
+            .collect::<Vec<_>>();
+
+        // closest edge
+        let closest_edge = screen_points
+            .windows(2)
+            .enumerate()
+            .map(|(i, w)| (i, distance_to_segment(interact_pos, w[0], w[1])))
+            .min_by(|a, b| a.1.total_cmp(&b.1));
+        if let Some((i, dist)) = closest_edge {
+            if dist < PICK_RADIUS {
+                let (a, b) = (screen_points[i], screen_points[i + 1]);
+                let ab = b - a;
+                let t = if ab.length_sq() > 0.0 {
+                    ((interact_pos - a).dot(ab) / ab.length_sq()).clamp(0.0, 1.0)
+                } else {
+                    0.0
+                };
+                let projected = self.dimensions.canvas_to_engine(from_screen * (a + ab * t));
+                return (i + 1, projected);
+            }
+        }
+
+        // extend the segment at the nearer end
+        match (screen_points.first(), screen_points.last()) {
+            (Some(first), Some(last))
+                if screen_points.len() > 1
+                    && first.distance(interact_pos) < last.distance(interact_pos) =>
+            {
+                (0, engine_pos)
+            }
+            _ => (points.len(), engine_pos),
+        }
+    }
+
+    pub fn editor_on_box_select_started(&mut self, from_screen: RectTransform, interact_pos: Pos2) {
+        let canvas_pos = from_screen * interact_pos;
+        self.editor_data.box_select = Some((canvas_pos, canvas_pos));
+    }
+
+    pub fn editor_on_box_select_dragged(&mut self, from_screen: RectTransform, current_pos: Pos2) {
+        if let Some((_, end)) = &mut self.editor_data.box_select {
+            *end = from_screen * current_pos;
+        }
+    }
+
+    /// Select all points of displayed segments inside the box
+    pub fn editor_on_box_select_stopped(&mut self) {
+        let Some((start, end)) = self.editor_data.box_select.take() else {
+            return;
+        };
+        let rect = Rect::from_two_pos(start, end);
+
+        let mut selected = vec![];
+        for (id, segment) in self.editor_data.segments.iter().filter(|(_, s)| s.selected) {
+            for (i, point) in segment.route1.iter().flatten().enumerate() {
+                if rect.contains(
+                    self.dimensions
+                        .engine_to_canvas(Pos2::new(point.x, point.y)),
+                ) {
+                    selected.push((id.clone(), i));
+                }
+            }
+        }
+        selected.sort();
+        self.editor_data.selected_points = selected;
+    }
+
+    /// Move all selected points by the drag offset from their original positions
+    pub(crate) fn move_selected_points(&mut self, delta: egui::Vec2) {
+        let moved = self
+            .editor_data
+            .drag_origins
+            .iter()
+            .map(|((id, i), origin)| {
+                let pos = Pos2::new(origin.x, origin.y) + delta;
+                let z = if self.editor_data.terrain_settings.auto_z {
+                    self.editor_point_z(id, pos)
+                } else {
+                    origin.z
+                };
+                ((id.clone(), *i), Pos3::new(pos.x, pos.y, z))
+            })
+            .collect::<Vec<_>>();
+
+        for ((id, i), position) in moved {
+            if let Some(point) = self
+                .editor_data
+                .segments
+                .get_mut(&id)
+                .and_then(|s| s.route1.as_mut())
+                .and_then(|r| r.get_mut(i))
+            {
+                *point = position;
+            }
+        }
+    }
+}
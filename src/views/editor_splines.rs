@@ -0,0 +1,312 @@
+use egui::Pos2;
+
+use crate::views::editor_panel::Pos3;
+use crate::TemplateApp;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, serde::Deserialize, serde::Serialize)]
+pub enum ESplineTool {
+    #[default]
+    CatmullRom,
+    Bezier,
+    Resample,
+    Simplify,
+    Reverse,
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct SplineSettings {
+    pub tool: ESplineTool,
+    /// points inserted between two points when smoothing
+    pub subdivisions: usize,
+    /// point spacing in units when resampling
+    pub spacing: f32,
+    /// maximum deviation in units when simplifying
+    pub epsilon: f32,
+    pub preview: bool,
+}
+
+impl Default for SplineSettings {
+    fn default() -> Self {
+        Self {
+            tool: ESplineTool::CatmullRom,
+            subdivisions: 4,
+            spacing: 512.0,
+            epsilon: 64.0,
+            preview: true,
+        }
+    }
+}
+
+fn lerp(a: &Pos3, b: &Pos3, t: f32) -> Pos3 {
+    Pos3::new(
+        a.x + (b.x - a.x) * t,
+        a.y + (b.y - a.y) * t,
+        a.z + (b.z - a.z) * t,
+    )
+}
+
+fn distance(a: &Pos3, b: &Pos3) -> f32 {
+    Pos2::new(a.x, a.y).distance(Pos2::new(b.x, b.y))
+}
+
+/// Centripetal Catmull-Rom spline through all points
+/// The knots are spaced by the square root of the point distances (Barry-Goldman),
+/// which keeps unevenly spaced points from overshooting or looping
+pub fn catmull_rom(points: &[Pos3], subdivisions: usize) -> Vec<Pos3> {
+    if points.len() < 3 {
+        return points.to_vec();
+    }
+
+    // mirror the neighbours at both ends
+    let last = points.len() - 1;
+    let first = lerp(&points[1], &points[0], 2.0);
+    let end = lerp(&points[last - 1], &points[last], 2.0);
+    let get = |i: isize| -> &Pos3 {
+        match i {
+            -1 => &first,
+            i if i as usize > last => &end,
+            i => &points[i as usize],
+        }
+    };
+    // coincident points would divide by zero
+    let knot = |a: &Pos3, b: &Pos3| distance(a, b).sqrt().max(1e-3);
+
+    let mut result = vec![];
+    for i in 0..last as isize {
+        let (p0, p1, p2, p3) = (get(i - 1), get(i), get(i + 1), get(i + 2));
+        let t1 = knot(p0, p1);
+        let t2 = t1 + knot(p1, p2);
+        let t3 = t2 + knot(p2, p3);
+        for s in 0..=subdivisions {
+            let t = t1 + (t2 - t1) * s as f32 / (subdivisions + 1) as f32;
+            let a1 = lerp(p0, p1, t / t1);
+            let a2 = lerp(p1, p2, (t - t1) / (t2 - t1));
+            let a3 = lerp(p2, p3, (t - t2) / (t3 - t2));
+            let b1 = lerp(&a1, &a2, t / t2);
+            let b2 = lerp(&a2, &a3, (t - t1) / (t3 - t1));
+            result.push(lerp(&b1, &b2, (t - t1) / (t2 - t1)));
+        }
+    }
+    result.push(points[last].clone());
+    result
+}
+
+/// Quadratic Bezier curves between edge midpoints, the inner points become control points
+pub fn bezier(points: &[Pos3], subdivisions: usize) -> Vec<Pos3> {
+    if points.len() < 3 {
+        return points.to_vec();
+    }
+
+    let mut result = vec![points[0].clone()];
+    for i in 1..points.len() - 1 {
+        let start = if i == 1 {
+            points[0].clone()
+        } else {
+            lerp(&points[i - 1], &points[i], 0.5)
+        };
+        let end = if i == points.len() - 2 {
+            points[i + 1].clone()
+        } else {
+            lerp(&points[i], &points[i + 1], 0.5)
+        };
+        let steps = subdivisions + 1;
+        for s in 1..=steps {
+            let t = s as f32 / steps as f32;
+            let a = lerp(&start, &points[i], t);
+            let b = lerp(&points[i], &end, t);
+            result.push(lerp(&a, &b, t));
+        }
+    }
+    result
+}
+
+/// Evenly spaced points along the polyline, keeping both ends
+pub fn resample(points: &[Pos3], spacing: f32) -> Vec<Pos3> {
+    if points.len() < 2 || spacing <= 0.0 {
+        return points.to_vec();
+    }
+
+    let mut result = vec![points[0].clone()];
+    let mut carry = 0.0;
+    for pair in points.windows(2) {
+        let length = distance(&pair[0], &pair[1]);
+        let mut d = spacing - carry;
+        while d < length {
+            result.push(lerp(&pair[0], &pair[1], d / length));
+            d += spacing;
+        }
+        carry = length - (d - spacing);
+    }
+
+    // don't end with a tiny step
+    if carry < spacing * 0.5 && result.len() > 1 {
+        result.pop();
+    }
+    result.push(points[points.len() - 1].clone());
+    result
+}
+
+/// Ramer-Douglas-Peucker simplification
+pub fn douglas_peucker(points: &[Pos3], epsilon: f32) -> Vec<Pos3> {
+    if points.len() < 3 {
+        return points.to_vec();
+    }
+
+    let first = Pos2::new(points[0].x, points[0].y);
+    let last = Pos2::new(points[points.len() - 1].x, points[points.len() - 1].y);
+    let (index, max_dist) = points[1..points.len() - 1]
+        .iter()
+        .enumerate()
+        .map(|(i, p)| {
+            (
+                i + 1,
+                crate::distance_to_segment(Pos2::new(p.x, p.y), first, last),
+            )
+        })
+        .fold((0, 0.0), |acc, x| if x.1 > acc.1 { x } else { acc });
+
+    if max_dist > epsilon {
+        let mut left = douglas_peucker(&points[..=index], epsilon);
+        let right = douglas_peucker(&points[index..], epsilon);
+        left.pop();
+        left.extend(right);
+        left
+    } else {
+        vec![points[0].clone(), points[points.len() - 1].clone()]
+    }
+}
+
+pub fn apply_spline_tool(points: &[Pos3], settings: &SplineSettings) -> Vec<Pos3> {
+    match settings.tool {
+        ESplineTool::CatmullRom => catmull_rom(points, settings.subdivisions),
+        ESplineTool::Bezier => bezier(points, settings.subdivisions),
+        ESplineTool::Resample => resample(points, settings.spacing),
+        ESplineTool::Simplify => douglas_peucker(points, settings.epsilon),
+        ESplineTool::Reverse => points.iter().rev().cloned().collect(),
+    }
+}
+
+impl TemplateApp {
+    /// Smoothing, resampling, simplification and reversing of the current segment
+    pub fn editor_spline_tools_ui(&mut self, ui: &mut egui::Ui) {
+        let Some(id) = self.editor_data.current_segment.clone() else {
+            self.editor_data.spline_preview = None;
+            return;
+        };
+        let points = self
+            .editor_data
+            .segments
+            .get(&id)
+            .and_then(|s| s.route1.clone())
+            .unwrap_or_default();
+
+        let settings = &mut self.editor_data.spline_settings;
+        egui::ComboBox::from_label("Tool")
+            .selected_text(format!("{:?}", settings.tool))
+            .show_ui(ui, |ui| {
+                for tool in [
+                    ESplineTool::CatmullRom,
+                    ESplineTool::Bezier,
+                    ESplineTool::Resample,
+                    ESplineTool::Simplify,
+                    ESplineTool::Reverse,
+                ] {
+                    ui.selectable_value(&mut settings.tool, tool, format!("{:?}", tool));
+                }
+            });
+        match settings.tool {
+            ESplineTool::CatmullRom | ESplineTool::Bezier => {
+                ui.add(egui::Slider::new(&mut settings.subdivisions, 1..=16).text("Subdivisions"));
+            }
+            ESplineTool::Resample => {
+                ui.add(
+                    egui::Slider::new(&mut settings.spacing, 16.0..=8192.0)
+                        .logarithmic(true)
+                        .text("Spacing"),
+                );
+            }
+            ESplineTool::Simplify => {
+                ui.add(
+                    egui::Slider::new(&mut settings.epsilon, 1.0..=2048.0)
+                        .logarithmic(true)
+                        .text("Tolerance"),
+                );
+            }
+            ESplineTool::Reverse => {}
+        }
+
+        let result = apply_spline_tool(&points, settings);
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut settings.preview, "Preview");
+            ui.label(format!("{} → {} points", points.len(), result.len()));
+        });
+
+        self.editor_data.spline_preview = self
+            .editor_data
+            .spline_settings
+            .preview
+            .then(|| (id.clone(), result.clone()));
+
+        if ui
+            .add_enabled(points.len() > 1, egui::Button::new("Apply"))
+            .clicked()
+        {
+            self.editor_data.begin_edit();
+            if let Some(segment) = self.editor_data.segments.get_mut(&id) {
+                segment.route1 = Some(result);
+            }
+            let description = format!("{:?} segment", self.editor_data.spline_settings.tool);
+            self.editor_data.commit_edit(&description);
+            self.editor_data.picked_point = None;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn catmull_rom_follows_a_circle() {
+        let radius = 4096.0;
+        let points: Vec<_> = (0..=6)
+            .map(|i| {
+                let a = i as f32 * std::f32::consts::PI / 12.0;
+                Pos3::new(radius * a.cos(), radius * a.sin(), 0.0)
+            })
+            .collect();
+        let result = catmull_rom(&points, 4);
+
+        assert_eq!(result.len(), 6 * 5 + 1);
+        // every input point is kept
+        for (i, p) in points.iter().enumerate() {
+            assert!(distance(&result[i * 5], p) < 1e-2);
+        }
+        // the mirrored end points straighten the outer spans
+        for p in &result[5..result.len() - 5] {
+            let r = (p.x * p.x + p.y * p.y).sqrt();
+            assert!(
+                (r - radius).abs() < 5.0,
+                "{:?} is {} off the circle",
+                p,
+                r - radius
+            );
+        }
+    }
+
+    #[test]
+    fn catmull_rom_does_not_overshoot_uneven_points() {
+        let points = [
+            Pos3::new(0.0, 0.0, 0.0),
+            Pos3::new(10.0, 0.0, 0.0),
+            Pos3::new(1000.0, 0.0, 0.0),
+            Pos3::new(1010.0, 0.0, 0.0),
+        ];
+        let result = catmull_rom(&points, 8);
+        for pair in result.windows(2) {
+            assert!(pair[1].x >= pair[0].x, "{:?} goes back", pair);
+            assert_eq!(pair[1].y, 0.0);
+        }
+    }
+}
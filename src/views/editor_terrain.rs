@@ -0,0 +1,224 @@
+use egui::{ahash::HashMap, Pos2};
+
+use crate::views::editor_panel::EditorData;
+use crate::{interpolate_height_at_engine, TemplateApp};
+
+#[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct TerrainSettings {
+    /// fill the height of new and moved points from the terrain
+    pub auto_z: bool,
+    /// height above the terrain for services without their own offset
+    pub default_offset: f32,
+    pub service_offsets: HashMap<String, f32>,
+    /// services that travel on water, overriding the guess from the name
+    pub water_services: HashMap<String, bool>,
+}
+
+impl Default for TerrainSettings {
+    fn default() -> Self {
+        Self {
+            auto_z: true,
+            default_offset: 0.0,
+            service_offsets: HashMap::default(),
+            water_services: HashMap::default(),
+        }
+    }
+}
+
+impl TerrainSettings {
+    pub fn offset(&self, service: Option<&str>) -> f32 {
+        service
+            .and_then(|s| self.service_offsets.get(s))
+            .copied()
+            .unwrap_or(self.default_offset)
+    }
+}
+
+/// Guess from the service name if it travels on water
+fn is_water_service_name(service: &str) -> bool {
+    let service = service.to_lowercase();
+    ["boat", "ship", "gondol"]
+        .iter()
+        .any(|s| service.contains(s))
+}
+
+impl EditorData {
+    /// Services that travel on water, boats stay at water level and may not cross land
+    pub fn is_water_service(&self, service: &str) -> bool {
+        self.terrain_settings
+            .water_services
+            .get(service)
+            .copied()
+            .unwrap_or_else(|| is_water_service_name(service))
+    }
+
+    /// The service of the first route that uses the segment
+    pub fn segment_service(&self, id: &str) -> Option<String> {
+        self.routes
+            .iter()
+            .find(|r| r.segments.iter().any(|s| s == id))
+            .map(|r| r.id.service.clone())
+    }
+
+    /// All services used by routes and ports
+    pub fn services(&self) -> Vec<String> {
+        let mut services = self
+            .routes
+            .iter()
+            .map(|r| r.id.service.clone())
+            .chain(self.ports.values().flat_map(|p| p.data.keys().cloned()))
+            .collect::<Vec<_>>();
+        services.sort();
+        services.dedup();
+        services
+    }
+}
+
+impl TemplateApp {
+    /// Height of a point for a service, boats stay at water level
+    pub fn editor_height_for_service(&self, service: Option<&str>, pos: Pos2) -> f32 {
+        let settings = &self.editor_data.terrain_settings;
+        let offset = settings.offset(service);
+        if service.is_some_and(|s| self.editor_data.is_water_service(s)) {
+            return offset;
+        }
+
+        // open sea has no landscape
+        interpolate_height_at_engine(&self.heights, &self.dimensions, pos).unwrap_or(0.0) + offset
+    }
+
+    /// Height of a new or moved point of a segment, 0 if automatic heights are off
+    pub fn editor_point_z(&self, segment_id: &str, pos: Pos2) -> f32 {
+        if !self.editor_data.terrain_settings.auto_z {
+            return 0.0;
+        }
+        let service = self.editor_data.segment_service(segment_id);
+        self.editor_height_for_service(service.as_deref(), pos)
+    }
+
+    /// Set the height of all points of the segments from the terrain
+    pub fn editor_reproject_z(&mut self, ids: &[String]) {
+        let mut updates = vec![];
+        for id in ids {
+            let service = self.editor_data.segment_service(id);
+            let Some(points) = self
+                .editor_data
+                .segments
+                .get(id)
+                .and_then(|s| s.route1.as_ref())
+            else {
+                continue;
+            };
+            let z = points
+                .iter()
+                .map(|p| self.editor_height_for_service(service.as_deref(), Pos2::new(p.x, p.y)))
+                .collect::<Vec<_>>();
+            updates.push((id.clone(), z));
+        }
+
+        self.editor_data.begin_edit();
+        for (id, z) in updates {
+            if let Some(route1) = self
+                .editor_data
+                .segments
+                .get_mut(&id)
+                .and_then(|s| s.route1.as_mut())
+            {
+                for (point, z) in route1.iter_mut().zip(z) {
+                    point.z = z;
+                }
+            }
+        }
+        self.editor_data.commit_edit("Re-project Z");
+    }
+
+    /// Height offsets per service and re-projecting segments
+    pub fn editor_terrain_ui(&mut self, ui: &mut egui::Ui) {
+        let services = self.editor_data.services();
+        let settings = &mut self.editor_data.terrain_settings;
+        ui.checkbox(&mut settings.auto_z, "Height from terrain")
+            .on_hover_text("Fill the height of new and moved points, boats stay at water level");
+        ui.horizontal(|ui| {
+            ui.add(egui::DragValue::new(&mut settings.default_offset).speed(1.0));
+            ui.label("Default offset");
+        });
+        for service in services {
+            let mut water = self.editor_data.is_water_service(&service);
+            let settings = &mut self.editor_data.terrain_settings;
+            ui.horizontal(|ui| {
+                let mut offset = settings.offset(Some(&service));
+                if ui
+                    .add(egui::DragValue::new(&mut offset).speed(1.0))
+                    .changed()
+                {
+                    settings.service_offsets.insert(service.clone(), offset);
+                }
+                ui.label(&service);
+                if settings.service_offsets.contains_key(&service)
+                    && ui
+                        .small_button("⟲")
+                        .on_hover_text("Use the default offset")
+                        .clicked()
+                {
+                    settings.service_offsets.remove(&service);
+                }
+                if ui
+                    .checkbox(&mut water, "Water")
+                    .on_hover_text("Travels on water")
+                    .changed()
+                {
+                    settings.water_services.insert(service.clone(), water);
+                }
+            });
+        }
+
+        ui.horizontal(|ui| {
+            let current = self.editor_data.current_segment.clone();
+            if ui
+                .add_enabled(current.is_some(), egui::Button::new("Re-project Z"))
+                .on_hover_text("Set the heights of the current segment from the terrain")
+                .clicked()
+            {
+                self.editor_reproject_z(&current.into_iter().collect::<Vec<_>>());
+            }
+            if ui
+                .button("Re-project all")
+                .on_hover_text("Set the heights of all segments from the terrain")
+                .clicked()
+            {
+                let ids = self
+                    .editor_data
+                    .segments
+                    .keys()
+                    .cloned()
+                    .collect::<Vec<_>>();
+                self.editor_reproject_z(&ids);
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn water_services_by_name_and_setting() {
+        let mut editor_data = EditorData::default();
+        assert!(editor_data.is_water_service("Gondolier"));
+        assert!(editor_data.is_water_service("T_Mw_Shipmaster"));
+        assert!(!editor_data.is_water_service("Caravaner"));
+
+        editor_data
+            .terrain_settings
+            .water_services
+            .insert("Caravaner".to_string(), true);
+        editor_data
+            .terrain_settings
+            .water_services
+            .insert("Gondolier".to_string(), false);
+        assert!(editor_data.is_water_service("Caravaner"));
+        assert!(!editor_data.is_water_service("Gondolier"));
+    }
+}
@@ -0,0 +1,263 @@
+use egui::Pos2;
+
+use crate::dimensions::Dimensions;
+use crate::views::editor_panel::{EditorData, Pos3, Route, RouteMetadata};
+use crate::{height_at_engine, TemplateApp};
+
+/// Maximum distance between points that should be at the same position
+pub const JOINT_TOLERANCE: f32 = 10.0;
+
+/// A problem found in a route, with the position to highlight on the map
+#[derive(Debug, Clone, PartialEq)]
+pub struct RouteError {
+    pub message: String,
+    pub position: Option<Pos2>,
+}
+
+fn to_pos2(p: &Pos3) -> Pos2 {
+    Pos2::new(p.x, p.y)
+}
+
+/// All positions a route can start or end at in a port
+fn get_port_positions(editor_data: &EditorData, port: &str, service: &str) -> Vec<Pos2> {
+    let Some(port) = editor_data.ports.get(port) else {
+        return vec![];
+    };
+    port.data
+        .iter()
+        .filter(|(s, _)| s.eq_ignore_ascii_case(service))
+        .flat_map(|(_, data)| {
+            [
+                Some(&data.position),
+                data.positionStart.as_ref(),
+                data.positionEnd.as_ref(),
+            ]
+        })
+        .flatten()
+        .map(to_pos2)
+        .collect()
+}
+
+pub fn validate_route(
+    editor_data: &EditorData,
+    route: &Route,
+    heights: &[f32],
+    dimensions: &Dimensions,
+) -> RouteMetadata {
+    let mut errors = vec![];
+
+    // all segments must exist
+    let mut path: Vec<Pos2> = vec![];
+    for id in &route.segments {
+        let Some(points) = editor_data
+            .segments
+            .get(id)
+            .and_then(|s| s.route1.as_ref())
+            .filter(|p| !p.is_empty())
+        else {
+            errors.push(RouteError {
+                message: format!("Segment '{}' is missing or empty", id),
+                position: path.last().copied(),
+            });
+            continue;
+        };
+
+        // consecutive segments share their endpoints
+        let first = to_pos2(&points[0]);
+        if let Some(end) = path.last() {
+            if end.distance(first) > JOINT_TOLERANCE {
+                errors.push(RouteError {
+                    message: format!(
+                        "Segment '{}' starts {:.0} units away from the previous segment",
+                        id,
+                        end.distance(first)
+                    ),
+                    position: Some(first),
+                });
+            }
+        }
+        path.extend(points.iter().map(to_pos2));
+    }
+
+    // the route starts and ends at its ports
+    for (port, point, what) in [
+        (&route.id.start, path.first(), "start"),
+        (&route.id.destination, path.last(), "destination"),
+    ] {
+        let Some(point) = point else {
+            continue;
+        };
+        let positions = get_port_positions(editor_data, port, &route.id.service);
+        if positions.is_empty() {
+            errors.push(RouteError {
+                message: format!("No {} port '{}' for {}", what, port, route.id.service),
+                position: Some(*point),
+            });
+        } else if !positions
+            .iter()
+            .any(|p| p.distance(*point) <= JOINT_TOLERANCE)
+        {
+            errors.push(RouteError {
+                message: format!("Route {} is not at port '{}'", what, port),
+                position: Some(*point),
+            });
+        }
+    }
+
+    // boats stay on water
+    if editor_data.is_water_service(&route.id.service) && !heights.is_empty() {
+        let mut on_land = false;
+        for pair in path.windows(2) {
+            let length = pair[0].distance(pair[1]);
+            let count = (length / 64.0).ceil().max(1.0) as usize;
+            for i in 0..=count {
+                let pos = pair[0].lerp(pair[1], i as f32 / count as f32);
+                let land = height_at_engine(heights, dimensions, pos).is_some_and(|z| z > 0.0);
+                if land && !on_land {
+                    errors.push(RouteError {
+                        message: format!("Crosses land at ({:.0}, {:.0})", pos.x, pos.y),
+                        position: Some(pos),
+                    });
+                }
+                on_land = land;
+            }
+        }
+    }
+
+    RouteMetadata { errors }
+}
+
+impl TemplateApp {
+    pub fn validate_routes(&mut self) {
+        self.editor_data.routes_metadata.clear();
+        for route in &self.editor_data.routes {
+            let metadata =
+                validate_route(&self.editor_data, route, &self.heights, &self.dimensions);
+            if metadata.is_valid() {
+                log::info!("Route '{}' is valid", route.id);
+            } else {
+                log::warn!(
+                    "Route '{}' is invalid: {} errors",
+                    route.id,
+                    metadata.errors.len()
+                );
+            }
+            self.editor_data
+                .routes_metadata
+                .insert(route.id.clone(), metadata);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::views::editor_panel::{Port, PortData, RouteId, Segment};
+
+    fn segment(id: &str, from: f32, to: f32) -> (String, Segment) {
+        (
+            id.to_string(),
+            Segment {
+                id: id.to_string(),
+                route1: Some(vec![Pos3::new(from, 0.0, 0.0), Pos3::new(to, 0.0, 0.0)]),
+                selected: false,
+            },
+        )
+    }
+
+    fn port(service: &str, x: f32) -> Port {
+        Port {
+            data: [(
+                service.to_string(),
+                PortData {
+                    position: Pos3::new(x, 0.0, 0.0),
+                    ..Default::default()
+                },
+            )]
+            .into_iter()
+            .collect(),
+        }
+    }
+
+    fn route(segments: &[&str]) -> Route {
+        Route {
+            id: RouteId {
+                start: "Vivec".to_string(),
+                destination: "Ebonheart".to_string(),
+                service: "Shipmaster".to_string(),
+            },
+            segments: segments.iter().map(|s| s.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn valid_route_has_no_errors() {
+        let mut editor_data = EditorData::default();
+        editor_data.segments.extend([segment("a", 0.0, 100.0)]);
+        editor_data.segments.extend([segment("b", 105.0, 300.0)]);
+        editor_data
+            .ports
+            .insert("Vivec".to_string(), port("shipmaster", 5.0));
+        editor_data
+            .ports
+            .insert("Ebonheart".to_string(), port("Shipmaster", 300.0));
+
+        let metadata = validate_route(
+            &editor_data,
+            &route(&["a", "b"]),
+            &[],
+            &Dimensions::default(),
+        );
+        assert_eq!(metadata.errors, vec![]);
+    }
+
+    #[test]
+    fn finds_missing_segments_gaps_and_ports() {
+        let mut editor_data = EditorData::default();
+        editor_data.segments.extend([segment("a", 0.0, 100.0)]);
+        editor_data.segments.extend([segment("b", 200.0, 300.0)]);
+        editor_data
+            .ports
+            .insert("Vivec".to_string(), port("Shipmaster", 5.0));
+
+        let metadata = validate_route(
+            &editor_data,
+            &route(&["a", "missing", "b"]),
+            &[],
+            &Dimensions::default(),
+        );
+        let errors: Vec<_> = metadata.errors.iter().map(|e| e.position).collect();
+        assert_eq!(
+            errors,
+            vec![
+                Some(Pos2::new(100.0, 0.0)),
+                Some(Pos2::new(200.0, 0.0)),
+                Some(Pos2::new(300.0, 0.0)),
+            ]
+        );
+        assert!(metadata.errors[0].message.contains("'missing'"));
+        assert!(metadata.errors[1].message.contains("100 units"));
+        assert!(metadata.errors[2]
+            .message
+            .starts_with("No destination port"));
+
+        // a port for the service that is too far away
+        editor_data
+            .ports
+            .insert("Ebonheart".to_string(), port("Shipmaster", 400.0));
+        let metadata = validate_route(
+            &editor_data,
+            &route(&["a", "b"]),
+            &[],
+            &Dimensions::default(),
+        );
+        let messages: Vec<_> = metadata.errors.iter().map(|e| e.message.as_str()).collect();
+        assert_eq!(
+            messages,
+            vec![
+                "Segment 'b' starts 100 units away from the previous segment",
+                "Route destination is not at port 'Ebonheart'",
+            ]
+        );
+    }
+}
@@ -0,0 +1,269 @@
+use egui::{emath::RectTransform, pos2, Color32, Rect, Sense, Vec2};
+use tes3::esp::TravelDestination;
+
+use crate::overlay::doors::is_interior_door;
+use crate::overlay::interior::{get_interior_bounds, get_interior_shapes, plan_pos};
+use crate::{cell_key_from_engine, CellReference, TemplateApp};
+
+/// Interior shown instead of the map, with its own pan and zoom
+#[derive(Debug, Clone)]
+pub struct InteriorView {
+    pub name: String,
+    pub references: Vec<CellReference>,
+    pub water_height: Option<f32>,
+    pub zoom: f32,
+    pub drag_offset: Vec2,
+}
+
+/// Navigation requested from the interior view
+enum EInteriorLink {
+    Close,
+    Follow(TravelDestination),
+}
+
+impl TemplateApp {
+    /// List of all loaded interior cells
+    pub fn interiors_panel(&mut self, ui: &mut egui::Ui, _ctx: &egui::Context) {
+        ui.heading("Interiors");
+
+        // search bar
+        ui.horizontal(|ui| {
+            ui.label("Filter: ");
+            ui.text_edit_singleline(&mut self.runtime_data.interior_filter);
+            // clear filter button
+            if ui.button("x").clicked() {
+                self.runtime_data.interior_filter.clear();
+            }
+        });
+
+        ui.separator();
+
+        let mut names = self
+            .interior_records
+            .values()
+            .map(|c| c.name.clone())
+            .collect::<Vec<_>>();
+        names.sort_by_key(|n| n.to_lowercase());
+
+        egui::ScrollArea::vertical()
+            .auto_shrink([false, true])
+            .show(ui, |ui| {
+                for name in names {
+                    // upper and lowercase search
+                    if !self.runtime_data.interior_filter.is_empty()
+                        && !name
+                            .to_lowercase()
+                            .contains(&self.runtime_data.interior_filter.to_lowercase())
+                    {
+                        continue;
+                    }
+
+                    let selected = self
+                        .runtime_data
+                        .interior_view
+                        .as_ref()
+                        .is_some_and(|v| v.name.eq_ignore_ascii_case(&name));
+                    if ui.selectable_label(selected, &name).clicked() {
+                        self.open_interior(&name);
+                    }
+                }
+            });
+    }
+
+    pub fn open_interior(&mut self, name: &str) {
+        let key = name.to_lowercase();
+        self.runtime_data.interior_view = Some(InteriorView {
+            name: name.to_owned(),
+            references: self
+                .interior_references
+                .get(&key)
+                .cloned()
+                .unwrap_or_default(),
+            water_height: self.interior_records.get(&key).and_then(|c| c.water_height),
+            zoom: 1.0,
+            drag_offset: Vec2::ZERO,
+        });
+    }
+
+    pub fn close_interior(&mut self) {
+        self.runtime_data.interior_view = None;
+    }
+
+    /// Open the interior behind a door or go back to the map at its exterior cell
+    fn follow_door(&mut self, destination: &TravelDestination) {
+        if destination.cell.is_empty() {
+            let cell = cell_key_from_engine(destination.translation[0], destination.translation[1]);
+            self.close_interior();
+            self.runtime_data.selected_ids = vec![cell];
+            self.runtime_data.pivot_id = Some(cell);
+            self.focus_cell(cell);
+        } else {
+            self.open_interior(&destination.cell);
+        }
+    }
+
+    /// Top-down plan of the references of an interior cell
+    pub fn interior_panel(&mut self, ui: &mut egui::Ui, ctx: &egui::Context) {
+        let Some(mut view) = self.runtime_data.interior_view.take() else {
+            return;
+        };
+        let link = self.interior_plan(ui, ctx, &mut view);
+        self.runtime_data.interior_view = Some(view);
+
+        match link {
+            Some(EInteriorLink::Close) => self.close_interior(),
+            Some(EInteriorLink::Follow(destination)) => self.follow_door(&destination),
+            None => {}
+        }
+    }
+
+    fn interior_plan(
+        &self,
+        ui: &mut egui::Ui,
+        ctx: &egui::Context,
+        view: &mut InteriorView,
+    ) -> Option<EInteriorLink> {
+        let mut link = None;
+
+        ui.horizontal(|ui| {
+            if ui.button("⬅ Map").clicked() {
+                link = Some(EInteriorLink::Close);
+            }
+            ui.heading(&view.name);
+        });
+        ui.horizontal(|ui| {
+            ui.label(format!("References: {}", view.references.len()));
+            ui.separator();
+            match view.water_height {
+                Some(h) => ui.label(format!("Water level: {:.0}", h)),
+                None => ui.label("No water"),
+            };
+        });
+
+        // door links
+        egui::CollapsingHeader::new("Doors")
+            .default_open(true)
+            .show(ui, |ui| {
+                for door in view.references.iter() {
+                    let Some(destination) = &door.destination else {
+                        continue;
+                    };
+
+                    ui.horizontal(|ui| {
+                        ui.label(&door.id);
+                        ui.label("→");
+                        let text = if is_interior_door(door) {
+                            destination.cell.clone()
+                        } else {
+                            let cell = cell_key_from_engine(
+                                destination.translation[0],
+                                destination.translation[1],
+                            );
+                            format!("Exterior {:?}", cell)
+                        };
+                        if ui.link(text).clicked() {
+                            link = Some(EInteriorLink::Follow(destination.clone()));
+                        }
+                    });
+                }
+            });
+
+        ui.separator();
+
+        let Some(bounds) = get_interior_bounds(&view.references) else {
+            ui.label("No references");
+            return link;
+        };
+        let bounds = bounds.expand(bounds.size().max_elem() * 0.05);
+
+        // painter
+        let (response, painter) =
+            ui.allocate_painter(ui.available_size_before_wrap(), Sense::click_and_drag());
+
+        // pan and zoom
+        if response.dragged() {
+            view.drag_offset += response.drag_delta();
+        }
+        if response.hovered() {
+            let delta = ctx.input(|i| i.zoom_delta());
+            if delta != 1.0 {
+                view.zoom = (view.zoom * delta).max(0.05);
+            }
+        }
+        if response.middle_clicked() {
+            view.zoom = 1.0;
+            view.drag_offset = Vec2::ZERO;
+        }
+
+        // fit the plan into the viewport
+        let viewport = response.rect;
+        let scale = (viewport.width() / bounds.width()).min(viewport.height() / bounds.height());
+        let size = bounds.size() * scale * view.zoom;
+        let center = viewport.center() + view.drag_offset;
+        let canvas = Rect::from_center_size(center, size);
+        let to_screen = RectTransform::from_to(bounds, canvas);
+
+        painter.rect_filled(viewport, 0.0, Color32::from_gray(20));
+        painter.extend(get_interior_shapes(
+            to_screen,
+            &view.references,
+            view.water_height,
+        ));
+
+        // scale bar
+        let bar_units = 512.0;
+        let bar_length = bar_units * scale * view.zoom;
+        let bar_start = pos2(viewport.left() + 10.0, viewport.bottom() - 10.0);
+        painter.line_segment(
+            [bar_start, bar_start + Vec2::new(bar_length, 0.0)],
+            egui::Stroke::new(2.0, Color32::WHITE),
+        );
+        painter.text(
+            bar_start + Vec2::new(0.0, -4.0),
+            egui::Align2::LEFT_BOTTOM,
+            format!("{} units", bar_units),
+            egui::FontId::proportional(12.0),
+            Color32::WHITE,
+        );
+
+        // tooltip
+        if let Some(pointer_pos) = response.hover_pos() {
+            let hovered = view
+                .references
+                .iter()
+                .map(|r| (r, (to_screen * plan_pos(r)).distance(pointer_pos)))
+                .filter(|(_, d)| *d < 8.0)
+                .min_by(|a, b| a.1.total_cmp(&b.1))
+                .map(|(r, _)| r);
+
+            if let Some(reference) = hovered {
+                if self.ui_data.show_tooltips {
+                    response.clone().on_hover_ui_at_pointer(|ui| {
+                        ui.label(format!("{} ({:?})", reference.id, reference.ref_type));
+                        ui.label(format!(
+                            "Position: ({:.0}, {:.0}, {:.0})",
+                            reference.position[0], reference.position[1], reference.position[2]
+                        ));
+                        ui.label(format!("Scale: {:.2}", reference.scale));
+                        if let Some(destination) = &reference.destination {
+                            if destination.cell.is_empty() {
+                                ui.label("Door to the exterior");
+                            } else {
+                                ui.label(format!("Door to: {}", destination.cell));
+                            }
+                        }
+                    });
+                }
+
+                // follow door links on click
+                if response.clicked() {
+                    if let Some(destination) = &reference.destination {
+                        link = Some(EInteriorLink::Follow(destination.clone()));
+                    }
+                }
+            }
+        }
+
+        link
+    }
+}
@@ -6,9 +6,12 @@ use log::info;
 use crate::app::TooltipInfo;
 use crate::overlay::cities::get_cities_shapes;
 use crate::overlay::conflicts::get_conflict_shapes;
+use crate::overlay::doors::{get_door_at, get_door_shapes, is_interior_door};
 use crate::overlay::grid::get_grid_shapes;
-use crate::overlay::regions::get_region_shapes;
-use crate::overlay::travel::get_travel_shapes;
+use crate::overlay::labels::get_label_shapes;
+use crate::overlay::references::{get_reference_at, get_reference_shapes};
+use crate::overlay::regions::{find_region, get_region_shapes};
+use crate::overlay::travel::{get_friendly_class_name, get_travel_shapes};
 use crate::*;
 
 impl TemplateApp {
@@ -25,15 +28,21 @@ impl TemplateApp {
 
     pub fn map_panel(&mut self, ui: &mut egui::Ui, ctx: &egui::Context) {
         // The central panel the region left after adding TopPanel's and SidePanel's
-        ui.heading(format!(
-            "Map (y: [{},{}]; x: [{},{}]; z: [{},{}])",
-            self.dimensions.min_y,
-            self.dimensions.max_y,
-            self.dimensions.min_x,
-            self.dimensions.max_x,
-            self.dimensions.min_z,
-            self.dimensions.max_z
-        ));
+        ui.horizontal(|ui| {
+            ui.heading(format!(
+                "Map (y: [{},{}]; x: [{},{}]; z: [{},{}])",
+                self.dimensions.min_y,
+                self.dimensions.max_y,
+                self.dimensions.min_x,
+                self.dimensions.max_x,
+                self.dimensions.min_z,
+                self.dimensions.max_z
+            ));
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                ui.toggle_value(&mut self.runtime_data.profile_open, "⛰ Profile");
+                ui.toggle_value(&mut self.runtime_data.measure_mode, "📏 Measure");
+            });
+        });
 
         ui.separator();
 
@@ -103,6 +112,26 @@ impl TemplateApp {
             from_screen = to_screen.inverse();
         }
 
+        // center on a focused position
+        if let Some((focus, zoom)) = self.transform_data.focus.take() {
+            self.transform_data.zoom = zoom;
+            size = base_size * self.transform_data.zoom;
+            let scale_x = size.x / real_width;
+            let scale_y = size.y / real_height;
+            let new_min = pos2(
+                viewport.center().x - focus.x * scale_x,
+                viewport.center().y - focus.y * scale_y,
+            );
+            self.transform_data.drag_offset = new_min;
+
+            min = self.transform_data.drag_offset;
+            canvas = Rect::from_min_size(min, size);
+            to_screen = RectTransform::from_to(from, canvas);
+            from_screen = to_screen.inverse();
+        }
+
+        self.transform_data.view_rect = from_screen.transform_rect(viewport);
+
         // paint maps
 
         let uv = Rect::from_min_max(pos2(0.0, 0.0), Pos2::new(1.0, 1.0));
@@ -124,6 +153,7 @@ impl TemplateApp {
                 &self.dimensions,
                 &self.regn_records,
                 &self.cell_records,
+                &self.ui_data.region_settings,
             );
             painter.extend(shapes);
         }
@@ -135,19 +165,62 @@ impl TemplateApp {
             let shapes = get_cities_shapes(to_screen, &self.dimensions, &self.cell_records);
             painter.extend(shapes);
         }
+        if self.ui_data.overlay_references {
+            let shapes = get_reference_shapes(
+                to_screen,
+                &self.dimensions,
+                &self.reference_records,
+                &self.ui_data.reference_filters,
+                painter.clip_rect(),
+                self.transform_data.zoom,
+            );
+            painter.extend(shapes);
+        }
+        if self.ui_data.overlay_doors {
+            let shapes = get_door_shapes(
+                to_screen,
+                &self.dimensions,
+                &self.reference_records,
+                &self.interior_records,
+                self.transform_data.zoom,
+            );
+            painter.extend(shapes);
+        }
+        let travel_colors = self.get_travel_class_colors();
         for class in self.travel_edges.keys() {
             if let Some(class_option) = self.ui_data.overlay_travel.get(class) {
                 if *class_option {
-                    let shapes =
-                        get_travel_shapes(to_screen, &self.dimensions, &self.travel_edges, class);
+                    let shapes = get_travel_shapes(
+                        to_screen,
+                        &self.dimensions,
+                        &self.travel_edges,
+                        class,
+                        travel_colors[class],
+                        self.runtime_data.selected_travel_edge.as_ref(),
+                    );
                     painter.extend(shapes);
                 }
             }
         }
+        if self.ui_data.overlay_validation {
+            let shapes = overlay::validation::get_validation_shapes(
+                to_screen,
+                &self.dimensions,
+                &self.cell_issues,
+            );
+            painter.extend(shapes);
+        }
         if self.ui_data.overlay_conflicts {
             let shapes = get_conflict_shapes(to_screen, &self.dimensions, &self.cell_conflicts);
             painter.extend(shapes);
         }
+        if let Some(label_settings) = self.get_effective_label_settings() {
+            let zoom = self.transform_data.zoom;
+            let labels = self.get_cached_labels(&label_settings);
+            let shapes = ctx
+                .fonts(|fonts| get_label_shapes(fonts, to_screen, labels, &label_settings, zoom));
+            painter.extend(shapes);
+        }
         // routes
         if self.editor_data.enabled {
             let shapes = overlay::mod_splines::get_segments_shapes(
@@ -155,7 +228,30 @@ impl TemplateApp {
                 &self.dimensions,
                 self.transform_data.zoom,
                 &self.editor_data,
-                &response.hover_pos(),
+            );
+            painter.extend(shapes);
+            let shapes = overlay::mod_splines::get_route_error_shapes(
+                to_screen,
+                &self.dimensions,
+                &self.editor_data,
+            );
+            painter.extend(shapes);
+            let shapes = overlay::mod_splines::get_playback_shapes(
+                to_screen,
+                &self.dimensions,
+                &self.editor_data,
+            );
+            painter.extend(shapes);
+        }
+
+        // measurement
+        if self.runtime_data.measure_mode {
+            let shapes = overlay::measure::get_measure_shapes(
+                to_screen,
+                &self.dimensions,
+                &self.runtime_data.measure_points,
+                self.runtime_data.measure_closed,
+                response.hover_pos(),
             );
             painter.extend(shapes);
         }
@@ -182,22 +278,77 @@ impl TemplateApp {
             painter.add(shape);
         }
 
+        // travel legend
+        let mut legend = self
+            .ui_data
+            .overlay_travel
+            .iter()
+            .filter(|(_, enabled)| **enabled)
+            .map(|(class, _)| {
+                (
+                    get_friendly_class_name(class, &self.class_names),
+                    travel_colors.get(class).copied().unwrap_or(Color32::RED),
+                )
+            })
+            .collect::<Vec<_>>();
+        if !legend.is_empty() {
+            legend.sort_by(|a, b| a.0.cmp(&b.0));
+            let row_height = 16.0;
+            let legend_rect = Rect::from_min_size(
+                pos2(
+                    response.rect.left() + 8.0,
+                    response.rect.bottom() - 8.0 - row_height * legend.len() as f32 - 8.0,
+                ),
+                vec2(180.0, row_height * legend.len() as f32 + 8.0),
+            );
+            painter.rect_filled(legend_rect, 4.0, Color32::from_black_alpha(180));
+            for (i, (name, color)) in legend.iter().enumerate() {
+                let y = legend_rect.top() + 4.0 + row_height * (i as f32 + 0.5);
+                painter.line_segment(
+                    [
+                        pos2(legend_rect.left() + 6.0, y),
+                        pos2(legend_rect.left() + 26.0, y),
+                    ],
+                    Stroke::new(3.0, *color),
+                );
+                painter.text(
+                    pos2(legend_rect.left() + 32.0, y),
+                    egui::Align2::LEFT_CENTER,
+                    name,
+                    egui::FontId::proportional(12.0),
+                    Color32::WHITE,
+                );
+            }
+        }
+
+        // minimap
+        let minimap_rect = self.minimap(ui, &painter, response.rect, from_screen);
+        let over_minimap = |pos: Pos2| minimap_rect.is_some_and(|r| r.contains(pos));
+
         // Responses
 
         // hover
+        self.editor_data.hovered_point = None;
         if let Some(pointer_pos) = response.hover_pos() {
-            self.on_hover(ui, &response, from_screen, pointer_pos);
+            if !over_minimap(pointer_pos) {
+                self.on_hover(ui, &response, from_screen, pointer_pos);
+            }
         }
 
         // panning
-        if !ui.ctx().input(|i| i.modifiers.ctrl) {
-            self.editor_data.selected_point = None;
+        if !ui.ctx().input(|i| i.modifiers.ctrl) || response.drag_stopped() {
+            self.editor_on_drag_stopped();
+        }
+        if response.drag_stopped() {
+            self.editor_on_box_select_stopped();
         }
 
         if response.drag_started() {
             if let Some(drag_start) = response.interact_pointer_pos() {
                 if ui.ctx().input(|i| i.modifiers.ctrl) {
                     self.on_ctrl_drag_started(from_screen, drag_start);
+                } else if self.editor_data.enabled && ui.ctx().input(|i| i.modifiers.shift) {
+                    self.editor_on_box_select_started(from_screen, drag_start);
                 } else {
                     self.transform_data.drag_start = drag_start;
                 }
@@ -206,6 +357,8 @@ impl TemplateApp {
             if let Some(current_pos) = response.interact_pointer_pos() {
                 if ui.ctx().input(|i| i.modifiers.ctrl) {
                     self.on_point_dragged(from_screen, current_pos);
+                } else if self.editor_data.box_select.is_some() {
+                    self.editor_on_box_select_dragged(from_screen, current_pos);
                 } else {
                     let delta = current_pos - self.transform_data.drag_start.to_vec2();
                     self.transform_data.drag_delta = Some(delta);
@@ -247,7 +400,11 @@ impl TemplateApp {
         }
 
         // click
-        if let Some(interact_pos) = painter.ctx().pointer_interact_pos() {
+        if let Some(interact_pos) = painter
+            .ctx()
+            .pointer_interact_pos()
+            .filter(|pos| response.contains_pointer() && !over_minimap(*pos))
+        {
             if ui.ctx().input(|i| i.modifiers.ctrl) {
                 if ui.ctx().input(|i| i.pointer.primary_clicked()) {
                     self.on_ctrl_clicked(from_screen, interact_pos);
@@ -306,10 +463,22 @@ impl TemplateApp {
     }
 
     fn on_click(&mut self, ui: &mut egui::Ui, from_screen: RectTransform, interact_pos: Pos2) {
+        if self.measure_on_click(from_screen, interact_pos) {
+            // capture
+            return;
+        }
         if self.editor_on_click(ui, from_screen, interact_pos) {
             // capture
             return;
         }
+        if self.doors_on_click(from_screen, interact_pos) {
+            // capture
+            return;
+        }
+        if self.travel_on_click(from_screen, interact_pos) {
+            // capture
+            return;
+        }
         self.map_on_click(ui, from_screen, interact_pos);
     }
 
@@ -411,8 +580,11 @@ impl TemplateApp {
             key,
             height: 0.0,
             region: String::new(),
+            region_record: None,
             cell_name: String::new(),
             conflicts: Vec::new(),
+            reference: None,
+            door: None,
             debug: String::new(),
         };
 
@@ -421,6 +593,9 @@ impl TemplateApp {
             tooltipinfo.cell_name.clone_from(&cell.name);
             if let Some(region) = cell.region.as_ref() {
                 tooltipinfo.region.clone_from(region);
+                if self.ui_data.overlay_region {
+                    tooltipinfo.region_record = find_region(&self.regn_records, region).cloned();
+                }
             }
         }
 
@@ -446,6 +621,33 @@ impl TemplateApp {
             }
         }
 
+        // get reference
+        if self.ui_data.overlay_references {
+            tooltipinfo.reference = get_reference_at(
+                from_screen.inverse(),
+                &self.dimensions,
+                &self.reference_records,
+                &self.ui_data.reference_filters,
+                key,
+                pointer_pos,
+                6.0,
+            )
+            .cloned();
+        }
+
+        // get door
+        if self.ui_data.overlay_doors {
+            tooltipinfo.door = get_door_at(
+                from_screen.inverse(),
+                &self.dimensions,
+                &self.reference_records,
+                key,
+                pointer_pos,
+                6.0,
+            )
+            .cloned();
+        }
+
         self.runtime_data.info = tooltipinfo;
 
         if self.ui_data.show_tooltips && ui.ui_contains_pointer() {
@@ -456,6 +658,84 @@ impl TemplateApp {
                 ui.label(format!("{:?} - {}", info.key, info.cell_name));
                 ui.label(format!("Region: {}", info.region));
 
+                // show the REGN data
+                if let Some(region) = &info.region_record {
+                    if !region.name.is_empty() {
+                        ui.label(format!("Name: {}", region.name));
+                    }
+                    let weather = &region.weather_chances;
+                    let chances = [
+                        ("Clear", weather.clear),
+                        ("Cloudy", weather.cloudy),
+                        ("Foggy", weather.foggy),
+                        ("Overcast", weather.overcast),
+                        ("Rain", weather.rain),
+                        ("Thunder", weather.thunder),
+                        ("Ash", weather.ash),
+                        ("Blight", weather.blight),
+                        ("Snow", weather.snow),
+                        ("Blizzard", weather.blizzard),
+                    ];
+                    ui.label("Weather:");
+                    for (name, chance) in chances.iter().filter(|(_, c)| *c > 0) {
+                        ui.label(format!("  - {}: {}%", name, chance));
+                    }
+                    if let Some(creature) = region.sleep_creature.as_ref().filter(|c| !c.is_empty())
+                    {
+                        ui.label(format!("Sleep creature: {}", creature));
+                    }
+                    if !region.sounds.is_empty() {
+                        ui.label("Sounds:");
+                        for sound in region.sounds.iter() {
+                            ui.label(format!("  - {} ({}%)", sound.sound_id, sound.chance));
+                        }
+                    }
+                }
+
+                // show the object under the cursor
+                if let Some(reference) = &info.reference {
+                    ui.label("________");
+                    ui.label(format!(
+                        "Object: {} ({:?})",
+                        reference.id, reference.ref_type
+                    ));
+                    ui.label(format!(
+                        "Position: ({:.0}, {:.0}, {:.0})",
+                        reference.position[0], reference.position[1], reference.position[2]
+                    ));
+                }
+
+                // show the door destination
+                if let Some(door) = &info.door {
+                    if let Some(destination) = &door.destination {
+                        ui.label("________");
+                        if is_interior_door(door) {
+                            ui.label(format!("Door: {}", door.id));
+                            if self
+                                .interior_records
+                                .contains_key(&destination.cell.to_lowercase())
+                            {
+                                ui.label(format!("Interior: {}", destination.cell));
+                            } else {
+                                ui.colored_label(
+                                    Color32::RED,
+                                    format!("Interior: {} (missing)", destination.cell),
+                                );
+                            }
+                            ui.label("Click to list its doors");
+                        } else {
+                            ui.label(format!("Teleport: {}", door.id));
+                            ui.label(format!(
+                                "Destination: {:?}",
+                                cell_key_from_engine(
+                                    destination.translation[0],
+                                    destination.translation[1]
+                                )
+                            ));
+                        }
+                    }
+                }
+
                 // only show if current background is heightmap
                 if self.ui_data.background == EBackground::HeightMap {
                     ui.label("________");
@@ -0,0 +1,186 @@
+use egui::Pos2;
+
+use crate::{
+    sample_elevation_profile, views::profile_view::plot_elevation_profile, TemplateApp, CELL_WIDTH,
+};
+
+// default run speed: speed 50, athletics 30
+// walk speed is fMoveCharWalkMin + (fMoveCharWalkMax - fMoveCharWalkMin) * speed / 100
+// run speed is walk speed * (fBaseRunMultiplier + fAthleticsRunBonus * athletics / 100)
+const WALK_SPEED: f32 = 75.0 + (200.0 - 75.0) * 0.5;
+pub const RUN_SPEED: f32 = WALK_SPEED * (1.75 + 0.3);
+
+/// Length of a polyline, closed if it is a polygon
+pub fn polyline_length(points: &[Pos2], closed: bool) -> f32 {
+    let mut length = points.windows(2).map(|p| p[0].distance(p[1])).sum::<f32>();
+    if closed && points.len() > 2 {
+        length += points[points.len() - 1].distance(points[0]);
+    }
+    length
+}
+
+/// Area of a simple polygon (shoelace formula)
+pub fn polygon_area(points: &[Pos2]) -> f32 {
+    if points.len() < 3 {
+        return 0.0;
+    }
+    let mut sum = 0.0;
+    for i in 0..points.len() {
+        let a = points[i];
+        let b = points[(i + 1) % points.len()];
+        sum += a.x * b.y - b.x * a.y;
+    }
+    sum.abs() / 2.0
+}
+
+pub fn format_duration(seconds: f32) -> String {
+    let seconds = seconds.round() as u32;
+    if seconds >= 3600 {
+        format!(
+            "{}h {:02}m {:02}s",
+            seconds / 3600,
+            (seconds % 3600) / 60,
+            seconds % 60
+        )
+    } else {
+        format!("{}m {:02}s", seconds / 60, seconds % 60)
+    }
+}
+
+impl TemplateApp {
+    /// Add a point to the measurement, returns true if the click was captured
+    pub fn measure_on_click(
+        &mut self,
+        from_screen: egui::emath::RectTransform,
+        interact_pos: Pos2,
+    ) -> bool {
+        if !self.runtime_data.measure_mode {
+            return false;
+        }
+
+        let canvas_pos = from_screen * interact_pos;
+        let engine_pos = self.dimensions.canvas_to_engine(canvas_pos);
+        self.runtime_data.measure_points.push(engine_pos);
+        true
+    }
+
+    pub fn measure_window(&mut self, ctx: &egui::Context) {
+        if !self.runtime_data.measure_mode {
+            return;
+        }
+
+        // remove the last point
+        if ctx.input(|i| i.key_pressed(egui::Key::Backspace)) && !ctx.wants_keyboard_input() {
+            self.runtime_data.measure_points.pop();
+        }
+
+        let mut open = true;
+        egui::Window::new("Measure")
+            .id(egui::Id::new("measure_window"))
+            .open(&mut open)
+            .default_width(320.0)
+            .show(ctx, |ui| {
+                ui.label("Click on the map to add points, backspace removes the last one");
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut self.runtime_data.measure_closed, "Polygon");
+                    if ui.button("Undo point").clicked() {
+                        self.runtime_data.measure_points.pop();
+                    }
+                    if ui.button("Clear").clicked() {
+                        self.runtime_data.measure_points.clear();
+                    }
+                });
+
+                ui.separator();
+
+                let points = &self.runtime_data.measure_points;
+                let closed = self.runtime_data.measure_closed;
+                let length = polyline_length(points, closed);
+
+                egui::Grid::new("measure_grid")
+                    .num_columns(2)
+                    .show(ui, |ui| {
+                        ui.label("Points:");
+                        ui.label(format!("{}", points.len()));
+                        ui.end_row();
+
+                        ui.label("Length:");
+                        ui.label(format!(
+                            "{:.0} units ({:.2} cells)",
+                            length,
+                            length / CELL_WIDTH
+                        ));
+                        ui.end_row();
+
+                        ui.label("Run time:")
+                            .on_hover_text(format!("At {:.0} units/s", RUN_SPEED));
+                        ui.label(format_duration(length / RUN_SPEED));
+                        ui.end_row();
+
+                        if closed {
+                            let area = polygon_area(points);
+                            ui.label("Area:");
+                            ui.label(format!(
+                                "{:.0} units² ({:.2} cells²)",
+                                area,
+                                area / (CELL_WIDTH * CELL_WIDTH)
+                            ));
+                            ui.end_row();
+                        }
+                    });
+
+                // elevation profile
+                if !self.heights.is_empty() && points.len() > 1 {
+                    ui.separator();
+                    ui.label("Elevation profile");
+                    let mut path = points.clone();
+                    if closed && points.len() > 2 {
+                        path.push(points[0]);
+                    }
+                    let samples =
+                        sample_elevation_profile(&self.heights, &self.dimensions, &path, 64.0);
+                    plot_elevation_profile(ui, &samples, 0.0, &[]);
+                }
+            });
+
+        if !open {
+            self.runtime_data.measure_mode = false;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use egui::pos2;
+
+    #[test]
+    fn polyline_length_closes_polygons() {
+        let points = [pos2(0.0, 0.0), pos2(3.0, 0.0), pos2(3.0, 4.0)];
+        assert_eq!(polyline_length(&points, false), 7.0);
+        assert_eq!(polyline_length(&points, true), 12.0);
+        // two points never close
+        assert_eq!(polyline_length(&points[..2], true), 3.0);
+        assert_eq!(polyline_length(&[], true), 0.0);
+    }
+
+    #[test]
+    fn polygon_area_ignores_winding() {
+        let square = [
+            pos2(0.0, 0.0),
+            pos2(2.0, 0.0),
+            pos2(2.0, 2.0),
+            pos2(0.0, 2.0),
+        ];
+        assert_eq!(polygon_area(&square), 4.0);
+        let reversed: Vec<_> = square.iter().rev().copied().collect();
+        assert_eq!(polygon_area(&reversed), 4.0);
+        assert_eq!(polygon_area(&square[..2]), 0.0);
+    }
+
+    #[test]
+    fn format_duration_switches_to_hours() {
+        assert_eq!(format_duration(59.6), "1m 00s");
+        assert_eq!(format_duration(3725.0), "1h 02m 05s");
+    }
+}
@@ -0,0 +1,76 @@
+use egui::{emath::RectTransform, pos2, Color32, Pos2, Rect, Sense, Stroke, StrokeKind, Vec2};
+
+use crate::{EMinimapCorner, TemplateApp};
+
+impl TemplateApp {
+    /// Overview inset of the whole world, click or drag to pan the map
+    /// Returns the rect of the inset so the map can ignore pointer events over it
+    pub fn minimap(
+        &mut self,
+        ui: &mut egui::Ui,
+        painter: &egui::Painter,
+        viewport: Rect,
+        from_screen: RectTransform,
+    ) -> Option<Rect> {
+        let settings = &self.ui_data.minimap_settings;
+        if !settings.enabled {
+            return None;
+        }
+
+        let real_width = self.dimensions.width() as f32;
+        let real_height = self.dimensions.height() as f32;
+        if real_width <= 0.0 || real_height <= 0.0 {
+            return None;
+        }
+
+        // fit the world into the configured size
+        let scale = settings.size / real_width.max(real_height);
+        let size = Vec2::new(real_width * scale, real_height * scale);
+        let margin = 8.0;
+        let min = match settings.corner {
+            EMinimapCorner::TopLeft => viewport.left_top() + Vec2::splat(margin),
+            EMinimapCorner::TopRight => {
+                pos2(viewport.right() - margin - size.x, viewport.top() + margin)
+            }
+            EMinimapCorner::BottomLeft => pos2(
+                viewport.left() + margin,
+                viewport.bottom() - margin - size.y,
+            ),
+            EMinimapCorner::BottomRight => viewport.right_bottom() - Vec2::splat(margin) - size,
+        };
+        let inset = Rect::from_min_size(min, size);
+
+        // paint
+        painter.rect_filled(inset.expand(2.0), 2.0, Color32::from_black_alpha(200));
+        if let Some(handle) = &self.background_handle {
+            let uv = Rect::from_min_max(pos2(0.0, 0.0), pos2(1.0, 1.0));
+            painter.image(handle.into(), inset, uv, Color32::WHITE);
+        }
+
+        let from = Rect::from_min_max(pos2(0.0, 0.0), pos2(real_width, real_height));
+        let to_inset = RectTransform::from_to(from, inset);
+        let view_rect = to_inset
+            .transform_rect(from_screen.transform_rect(viewport))
+            .intersect(inset);
+        if view_rect.is_positive() {
+            painter.rect_stroke(
+                view_rect,
+                0.0,
+                Stroke::new(1.5, Color32::YELLOW),
+                StrokeKind::Middle,
+            );
+        }
+
+        // pan
+        let response = ui.interact(inset, ui.id().with("minimap"), Sense::click_and_drag());
+        if response.clicked() || response.dragged() {
+            if let Some(pointer_pos) = response.interact_pointer_pos() {
+                let canvas_pos: Pos2 = to_inset.inverse() * pointer_pos.clamp(inset.min, inset.max);
+                self.transform_data.focus = Some((canvas_pos, self.transform_data.zoom));
+                ui.ctx().request_repaint();
+            }
+        }
+
+        Some(inset)
+    }
+}
@@ -1,5 +1,24 @@
+pub mod bookmarks_panel;
 pub mod cell_panel;
+pub mod doors_panel;
+pub mod editor_authoring;
+pub mod editor_formats;
+pub mod editor_geojson;
+pub mod editor_history;
+pub mod editor_io;
 pub mod editor_panel;
+pub mod editor_pathfinding;
+pub mod editor_playback;
+pub mod editor_selection;
+pub mod editor_splines;
+pub mod editor_terrain;
+pub mod editor_validation;
+pub mod interior_view;
 pub mod map_view;
+pub mod measure;
+pub mod minimap;
 pub mod plugins_panel;
+pub mod profile_view;
+pub mod search;
 pub mod settings;
+pub mod travel_panel;
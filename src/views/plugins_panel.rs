@@ -1,7 +1,13 @@
-use std::collections::hash_map::Entry;
+use std::{
+    collections::{hash_map::Entry, HashSet, VecDeque},
+    hash::Hash,
+};
 
 use log::{info, warn};
-use tes3::esp::{Cell, Landscape, LandscapeTexture, Npc, Plugin, Region};
+use tes3::esp::{
+    Activator, Cell, Class, Container, Creature, Door, Header, Landscape, LandscapeTexture, Light,
+    Npc, Plugin, Region, Static, TravelDestination,
+};
 
 use crate::*;
 
@@ -133,9 +139,15 @@ impl TemplateApp {
         self.ltex_records.clear();
         self.regn_records.clear();
         self.travel_edges.clear();
+        self.class_names.clear();
         self.ui_data.overlay_travel.clear();
         self.cell_records.clear();
+        self.reference_records.clear();
+        self.interior_records.clear();
+        self.interior_references.clear();
         self.cell_conflicts.clear();
+        self.label_cache = None;
+        self.runtime_data.search_results = None;
 
         // load plugins into memory
         let mut land_records: HashMap<CellKey, Landscape> = HashMap::default();
@@ -143,8 +155,15 @@ impl TemplateApp {
         let mut cells: HashMap<CellKey, Cell> = HashMap::default();
         //let mut land_id_map: HashMap<String, CellKey> = HashMap::default();
         let mut cell_conflicts: HashMap<CellKey, Vec<u64>> = HashMap::default();
-        let mut travels: HashMap<String, (Vec<CellKey>, String)> = HashMap::default();
-        let mut npcs: HashMap<String, CellKey> = HashMap::default();
+        let mut travels: HashMap<String, (Vec<TravelDestination>, String, String)> =
+            HashMap::default();
+        let mut references: HashMap<CellKey, Vec<CellReference>> = HashMap::default();
+        let mut interiors: HashMap<String, Cell> = HashMap::default();
+        let mut interior_references: HashMap<String, Vec<CellReference>> = HashMap::default();
+        // where each reference currently is, keyed by its plugin and index
+        let mut reference_cells: HashMap<(String, u32), CellKey> = HashMap::default();
+        let mut interior_reference_cells: HashMap<(String, u32), String> = HashMap::default();
+        let mut base_types: HashMap<String, ERefType> = HashMap::default();
 
         let enabled_plugins: Vec<&PluginViewModel> = self
             .plugins
@@ -161,7 +180,19 @@ impl TemplateApp {
                 .load_path_filtered(&path, |tag| {
                     matches!(
                         &tag,
-                        b"TES3" | b"LAND" | b"LTEX" | b"CELL" | b"NPC_" | b"REGN"
+                        b"TES3"
+                            | b"LAND"
+                            | b"LTEX"
+                            | b"CELL"
+                            | b"NPC_"
+                            | b"REGN"
+                            | b"CREA"
+                            | b"CONT"
+                            | b"DOOR"
+                            | b"LIGH"
+                            | b"ACTI"
+                            | b"STAT"
+                            | b"CLAS"
                     )
                 })
                 .is_ok()
@@ -172,46 +203,76 @@ impl TemplateApp {
                     vm.hash
                 );
 
+                // reference sources, the plugin itself for index 0 and its masters after
+                let mut sources = vec![path
+                    .file_name()
+                    .map(|n| n.to_string_lossy().to_lowercase())
+                    .unwrap_or_default()];
+                if let Some(header) = plugin.objects_of_type::<Header>().next() {
+                    sources.extend(header.masters.iter().map(|(m, _)| m.to_lowercase()));
+                }
+
                 // add travels
                 for npc in plugin.objects_of_type::<Npc>() {
-                    let travel_destinations = npc.travel_destinations.clone();
-                    if !travel_destinations.is_empty() {
-                        let mut travel_destination_cells: Vec<CellKey> = vec![];
-                        for d in travel_destinations {
-                            let mut x = (d.translation[0] / CELL_WIDTH) as i32;
-                            // TODO fix this shit
-                            if x < 0 {
-                                x -= 1;
-                            }
-                            let mut y = (d.translation[1] / CELL_WIDTH) as i32;
-                            // TODO fix this shit
-                            if y < 0 {
-                                y -= 1;
-                            }
-
-                            travel_destination_cells.push((x, y));
-                        }
-
-                        // get npc class
-                        let class = &npc.class;
-                        travels.insert(npc.id.clone(), (travel_destination_cells, class.clone()));
+                    if !npc.travel_destinations.is_empty() {
+                        let destinations = npc.travel_destinations.clone();
+                        travels.insert(
+                            npc.id.to_lowercase(),
+                            (destinations, npc.class.clone(), npc.name.clone()),
+                        );
+                    } else {
+                        // overridden without travel services
+                        travels.remove(&npc.id.to_lowercase());
                     }
                 }
 
+                // add class names
+                for class in plugin.objects_of_type::<Class>() {
+                    self.class_names
+                        .insert(class.id.to_lowercase(), class.name.clone());
+                }
+
+                // add base record types
+                for npc in plugin.objects_of_type::<Npc>() {
+                    base_types.insert(npc.id.to_lowercase(), ERefType::Npc);
+                }
+                for creature in plugin.objects_of_type::<Creature>() {
+                    base_types.insert(creature.id.to_lowercase(), ERefType::Creature);
+                }
+                for container in plugin.objects_of_type::<Container>() {
+                    base_types.insert(container.id.to_lowercase(), ERefType::Container);
+                }
+                for door in plugin.objects_of_type::<Door>() {
+                    base_types.insert(door.id.to_lowercase(), ERefType::Door);
+                }
+                for light in plugin.objects_of_type::<Light>() {
+                    base_types.insert(light.id.to_lowercase(), ERefType::Light);
+                }
+                for activator in plugin.objects_of_type::<Activator>() {
+                    base_types.insert(activator.id.to_lowercase(), ERefType::Activator);
+                }
+                for stat in plugin.objects_of_type::<Static>() {
+                    base_types.insert(stat.id.to_lowercase(), ERefType::Static);
+                }
+
                 // add Cells
                 for cell in plugin.objects_of_type::<Cell>() {
                     if cell.is_interior() {
+                        let name = cell.name.to_lowercase();
+                        merge_references(
+                            &mut interior_references,
+                            &mut interior_reference_cells,
+                            &name,
+                            cell,
+                            &sources,
+                            |_| None,
+                        );
+                        interiors.insert(name, cell.clone());
                         continue;
                     }
 
                     let key = (cell.data.grid.0, cell.data.grid.1);
 
-                    for (npc_id, _) in travels.clone() {
-                        if cell.references.iter().any(|p| p.1.id == npc_id) {
-                            npcs.insert(npc_id, key);
-                        }
-                    }
-
                     if let Entry::Vacant(e) = cell_conflicts.entry(key) {
                         e.insert(vec![vm.hash]);
                     } else {
@@ -220,6 +281,15 @@ impl TemplateApp {
                         cell_conflicts.insert(key, value);
                     }
 
+                    merge_references(
+                        &mut references,
+                        &mut reference_cells,
+                        &key,
+                        cell,
+                        &sources,
+                        Some,
+                    );
+
                     cells.insert(key, cell.clone());
                 }
 
@@ -246,24 +316,61 @@ impl TemplateApp {
         }
 
         // travel overlay
-        let mut edges: Vec<(String, (CellKey, CellKey))> = vec![];
-        for (key, start) in npcs.clone() {
-            if let Some((dest, class)) = travels.get(&key) {
-                for d in dest {
-                    if !edges.contains(&(class.to_string(), (*d, start))) {
-                        edges.push((class.to_string(), (start, *d)));
-                    }
+        // edges go from the NPC reference to the exact destination
+        // interior coordinates are not on the map, use the door out instead
+        let exterior_npcs = references.values().flatten().map(|r| (r, None));
+        let interior_npcs = interior_references
+            .iter()
+            .flat_map(|(cell, references)| references.iter().map(move |r| (r, Some(cell))));
+        let mut ordered_edges: HashMap<String, Vec<TravelEdge>> = HashMap::default();
+        for (reference, cell) in exterior_npcs.chain(interior_npcs) {
+            let Some((destinations, class, name)) = travels.get(&reference.id.to_lowercase())
+            else {
+                continue;
+            };
+
+            let (start, npc_interior) = match cell {
+                None => (reference.position, None),
+                Some(cell) => {
+                    let Some(position) = get_exterior_position(&interior_references, cell) else {
+                        info!(
+                            "Skipping travel from {} in {}, no exterior door found",
+                            reference.id, cell
+                        );
+                        continue;
+                    };
+                    let name = interiors.get(cell).map_or(cell, |c| &c.name);
+                    (position, Some(name.clone()))
                 }
-            }
-        }
-        edges.dedup();
-        let mut ordered_edges = HashMap::default();
-        for (class, _pairs) in edges.iter() {
-            ordered_edges.insert(class.to_string(), vec![]);
-        }
-        for (class, pair) in edges {
-            if let Some(v) = ordered_edges.get_mut(&class) {
-                v.push(pair);
+            };
+
+            for destination in destinations {
+                let (position, interior) = if destination.cell.is_empty() {
+                    (destination.translation, None)
+                } else if let Some(position) =
+                    get_exterior_position(&interior_references, &destination.cell)
+                {
+                    (position, Some(destination.cell.clone()))
+                } else {
+                    info!(
+                        "Skipping travel from {} to {}, no exterior door found",
+                        reference.id, destination.cell
+                    );
+                    continue;
+                };
+
+                ordered_edges
+                    .entry(class.clone())
+                    .or_default()
+                    .push(TravelEdge {
+                        npc_id: reference.id.clone(),
+                        npc_name: name.clone(),
+                        class: class.clone(),
+                        start,
+                        destination: position,
+                        npc_interior: npc_interior.clone(),
+                        interior,
+                    });
             }
         }
         self.ui_data.overlay_travel = ordered_edges.keys().map(|k| (k.clone(), false)).collect();
@@ -274,8 +381,237 @@ impl TemplateApp {
             self.cell_conflicts.insert(*k, v.to_vec());
         }
 
+        // resolve the base record types now that all plugins are loaded
+        for reference in references
+            .values_mut()
+            .chain(interior_references.values_mut())
+            .flatten()
+        {
+            if let Some(ref_type) = base_types.get(&reference.id.to_lowercase()) {
+                reference.ref_type = *ref_type;
+            }
+        }
+
         self.land_records = land_records;
         self.cell_records = cells;
+        self.reference_records = references;
+        self.interior_records = interiors;
+        self.interior_references = interior_references;
+        self.update_travel_graph();
+        self.cell_issues =
+            validation::validate_cells(&self.cell_records, &self.land_records, &self.regn_records);
         // self.land_ids = land_id_map;
     }
 }
+
+/// Add the references of a cell record, overriding the ones from masters
+/// Deleted references are removed and moved ones end up in the cell they moved to
+fn merge_references<K: Hash + Eq + Clone>(
+    cell_references: &mut HashMap<K, Vec<CellReference>>,
+    reference_cells: &mut HashMap<(String, u32), K>,
+    key: &K,
+    cell: &Cell,
+    sources: &[String],
+    moved_key: impl Fn(CellKey) -> Option<K>,
+) {
+    for reference in cell.references.values() {
+        let Some(source) = sources.get(reference.mast_index as usize) else {
+            warn!(
+                "Reference {} has an unknown master index {}",
+                reference.id, reference.mast_index
+            );
+            continue;
+        };
+
+        // drop the version from earlier plugins wherever it is
+        let reference_key = (source.clone(), reference.refr_index);
+        if let Some(previous) = reference_cells.remove(&reference_key) {
+            if let Some(references) = cell_references.get_mut(&previous) {
+                references.retain(|r| r.refr_index != reference.refr_index || &r.source != source);
+            }
+        }
+        if reference.deleted.unwrap_or_default() {
+            continue;
+        }
+
+        let target = reference
+            .moved_cell
+            .and_then(&moved_key)
+            .unwrap_or_else(|| key.clone());
+        cell_references
+            .entry(target.clone())
+            .or_default()
+            .push(CellReference {
+                id: reference.id.clone(),
+                source: source.clone(),
+                refr_index: reference.refr_index,
+                position: reference.translation,
+                rotation: reference.rotation,
+                scale: reference.scale.unwrap_or(1.0),
+                ref_type: ERefType::Other,
+                destination: reference.destination.clone(),
+            });
+        reference_cells.insert(reference_key, target);
+    }
+}
+
+/// Destination of the nearest door leading out of an interior, following doors through other interiors
+fn get_exterior_position(
+    interior_references: &HashMap<String, Vec<CellReference>>,
+    cell: &str,
+) -> Option<[f32; 3]> {
+    let mut visited = HashSet::from([cell.to_lowercase()]);
+    let mut queue = VecDeque::from([cell.to_lowercase()]);
+    while let Some(name) = queue.pop_front() {
+        let Some(references) = interior_references.get(&name) else {
+            continue;
+        };
+        for destination in references.iter().filter_map(|r| r.destination.as_ref()) {
+            if destination.cell.is_empty() {
+                return Some(destination.translation);
+            }
+            let next = destination.cell.to_lowercase();
+            if visited.insert(next.clone()) {
+                queue.push_back(next);
+            }
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use tes3::esp::Reference;
+
+    use super::*;
+
+    fn cell_with(references: Vec<Reference>) -> Cell {
+        Cell {
+            references: references
+                .into_iter()
+                .map(|r| ((r.mast_index, r.refr_index), r))
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    fn reference(mast_index: u32, refr_index: u32, id: &str) -> Reference {
+        Reference {
+            mast_index,
+            refr_index,
+            id: id.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn merge_references_overrides_by_master_and_index() {
+        let mut references = HashMap::default();
+        let mut locations = HashMap::default();
+        let master = vec!["morrowind.esm".to_string()];
+        let plugin = vec!["mod.esp".to_string(), "morrowind.esm".to_string()];
+
+        let cell = cell_with(vec![reference(0, 1, "chair"), reference(0, 2, "table")]);
+        merge_references(
+            &mut references,
+            &mut locations,
+            &(0, 0),
+            &cell,
+            &master,
+            Some,
+        );
+        // the plugin's own index 1 is a new reference, the master's index 1 is overridden
+        let cell = cell_with(vec![reference(0, 1, "lamp"), reference(1, 1, "chair_red")]);
+        merge_references(
+            &mut references,
+            &mut locations,
+            &(0, 0),
+            &cell,
+            &plugin,
+            Some,
+        );
+
+        let mut ids: Vec<_> = references[&(0, 0)].iter().map(|r| r.id.as_str()).collect();
+        ids.sort();
+        assert_eq!(ids, vec!["chair_red", "lamp", "table"]);
+    }
+
+    #[test]
+    fn merge_references_removes_deleted_and_moves_references() {
+        let mut references = HashMap::default();
+        let mut locations = HashMap::default();
+        let master = vec!["morrowind.esm".to_string()];
+        let plugin = vec!["mod.esp".to_string(), "morrowind.esm".to_string()];
+
+        let cell = cell_with(vec![reference(0, 1, "chair"), reference(0, 2, "table")]);
+        merge_references(
+            &mut references,
+            &mut locations,
+            &(0, 0),
+            &cell,
+            &master,
+            Some,
+        );
+
+        let mut deleted = reference(1, 1, "chair");
+        deleted.deleted = Some(true);
+        let mut moved = reference(1, 2, "table");
+        moved.moved_cell = Some((1, 0));
+        let cell = cell_with(vec![deleted, moved]);
+        merge_references(
+            &mut references,
+            &mut locations,
+            &(0, 0),
+            &cell,
+            &plugin,
+            Some,
+        );
+
+        assert!(references[&(0, 0)].is_empty());
+        assert_eq!(references[&(1, 0)].len(), 1);
+        assert_eq!(references[&(1, 0)][0].source, "morrowind.esm");
+        assert_eq!(locations[&("morrowind.esm".to_string(), 2)], (1, 0));
+    }
+
+    fn door(id: &str, cell: &str, translation: [f32; 3]) -> CellReference {
+        CellReference {
+            id: id.to_string(),
+            source: "mod.esp".to_string(),
+            refr_index: 1,
+            position: [0.0; 3],
+            rotation: [0.0; 3],
+            scale: 1.0,
+            ref_type: ERefType::Door,
+            destination: Some(TravelDestination {
+                translation,
+                rotation: [0.0; 3],
+                cell: cell.to_string(),
+            }),
+        }
+    }
+
+    #[test]
+    fn exterior_position_follows_interior_doors() {
+        let interior_references = HashMap::from([
+            (
+                "guild, upstairs".to_string(),
+                vec![door("stairs", "Guild, Hall", [0.0; 3])],
+            ),
+            (
+                "guild, hall".to_string(),
+                vec![
+                    door("stairs", "Guild, Upstairs", [0.0; 3]),
+                    door("front", "", [4096.0, 8192.0, 0.0]),
+                ],
+            ),
+            ("sealed".to_string(), vec![door("loop", "Sealed", [0.0; 3])]),
+        ]);
+
+        assert_eq!(
+            get_exterior_position(&interior_references, "Guild, Upstairs"),
+            Some([4096.0, 8192.0, 0.0])
+        );
+        assert_eq!(get_exterior_position(&interior_references, "Sealed"), None);
+        assert_eq!(get_exterior_position(&interior_references, "Missing"), None);
+    }
+}
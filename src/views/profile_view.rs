@@ -0,0 +1,264 @@
+use egui::{pos2, Color32, Pos2, Rect, Sense, Shape, Stroke, Ui, Vec2};
+
+use crate::{sample_elevation_profile, TemplateApp};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum EProfileSource {
+    /// the line drawn with the measurement tool
+    #[default]
+    Measure,
+    /// the current Immersive Travel segment
+    Segment,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ETravelMode {
+    #[default]
+    Land,
+    Water,
+}
+
+/// Distance ranges where a path leaves its medium: land for boats, water for land routes
+pub fn get_profile_flags(
+    samples: &[(f32, Option<f32>)],
+    mode: ETravelMode,
+    water_level: f32,
+) -> Vec<(f32, f32)> {
+    let mut flags: Vec<(f32, f32)> = vec![];
+    let mut start = None;
+
+    for (distance, z) in samples {
+        let bad = z.is_some_and(|z| match mode {
+            ETravelMode::Water => z > water_level,
+            ETravelMode::Land => z < water_level,
+        });
+        match (bad, start) {
+            (true, None) => start = Some(*distance),
+            (false, Some(s)) => {
+                flags.push((s, *distance));
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    if let (Some(s), Some((distance, _))) = (start, samples.last()) {
+        flags.push((s, *distance));
+    }
+
+    flags
+}
+
+/// Plot terrain height against distance along a path
+/// `flags` are distance ranges that are drawn highlighted
+pub fn plot_elevation_profile(
+    ui: &mut Ui,
+    samples: &[(f32, Option<f32>)],
+    water_level: f32,
+    flags: &[(f32, f32)],
+) {
+    let known = samples.iter().filter_map(|(_, z)| *z).collect::<Vec<_>>();
+    let (Some(length), false) = (samples.last().map(|s| s.0), known.is_empty()) else {
+        ui.label("No terrain data along the path");
+        return;
+    };
+    if length <= 0.0 {
+        return;
+    }
+
+    let min_z = known.iter().copied().fold(water_level, f32::min);
+    let max_z = known.iter().copied().fold(water_level, f32::max);
+    let range = (max_z - min_z).max(1.0);
+
+    let (response, painter) =
+        ui.allocate_painter(Vec2::new(ui.available_width(), 140.0), Sense::hover());
+    let rect = response.rect;
+    painter.rect_filled(rect, 2.0, Color32::from_black_alpha(120));
+
+    let plot = rect.shrink(4.0);
+    let to_plot = |distance: f32, z: f32| {
+        pos2(
+            plot.left() + distance / length * plot.width(),
+            plot.bottom() - (z - min_z) / range * plot.height(),
+        )
+    };
+
+    // flagged ranges
+    for (start, end) in flags {
+        let flag_rect = Rect::from_x_y_ranges(
+            to_plot(*start, 0.0).x..=to_plot(*end, 0.0).x.max(to_plot(*start, 0.0).x + 1.0),
+            plot.y_range(),
+        );
+        painter.rect_filled(
+            flag_rect,
+            0.0,
+            Color32::from_rgba_unmultiplied(255, 0, 0, 60),
+        );
+    }
+
+    // water level
+    let water_y = to_plot(0.0, water_level).y;
+    painter.line_segment(
+        [pos2(plot.left(), water_y), pos2(plot.right(), water_y)],
+        Stroke::new(1.0, Color32::from_rgb(60, 120, 255)),
+    );
+
+    // terrain, gaps where there is no landscape
+    let stroke = Stroke::new(1.5, Color32::from_rgb(120, 220, 120));
+    let mut line = vec![];
+    for (distance, z) in samples {
+        if let Some(z) = z {
+            line.push(to_plot(*distance, *z));
+        } else if !line.is_empty() {
+            painter.add(Shape::line(std::mem::take(&mut line), stroke));
+        }
+    }
+    if !line.is_empty() {
+        painter.add(Shape::line(line, stroke));
+    }
+
+    // hover readout
+    if let Some(pos) = response.hover_pos() {
+        let distance = ((pos.x - plot.left()) / plot.width() * length).clamp(0.0, length);
+        if let Some((d, Some(z))) = samples
+            .iter()
+            .min_by(|a, b| (a.0 - distance).abs().total_cmp(&(b.0 - distance).abs()))
+        {
+            let x = to_plot(*d, 0.0).x;
+            painter.line_segment(
+                [pos2(x, plot.top()), pos2(x, plot.bottom())],
+                Stroke::new(1.0, Color32::GRAY),
+            );
+            response.on_hover_text_at_pointer(format!("{:.0} units: z = {:.0}", d, z));
+        }
+    }
+
+    ui.label(format!(
+        "Height: {:.0} to {:.0}, water at {:.0}",
+        min_z, max_z, water_level
+    ));
+}
+
+impl TemplateApp {
+    /// Path of the current profile source in engine coordinates and the detected travel mode
+    fn get_profile_path(&self) -> (Vec<Pos2>, Option<ETravelMode>) {
+        match self.runtime_data.profile_source {
+            EProfileSource::Measure => (self.runtime_data.measure_points.clone(), None),
+            EProfileSource::Segment => {
+                let Some(id) = &self.editor_data.current_segment else {
+                    return (vec![], None);
+                };
+                let points = self
+                    .editor_data
+                    .segments
+                    .get(id)
+                    .and_then(|s| s.route1.as_ref())
+                    .map(|route| route.iter().map(|p| Pos2::new(p.x, p.y)).collect())
+                    .unwrap_or_default();
+                // the service of the routes using this segment
+                let mode = self
+                    .editor_data
+                    .routes
+                    .iter()
+                    .find(|r| r.segments.contains(id))
+                    .map(|r| {
+                        if self.editor_data.is_water_service(&r.id.service) {
+                            ETravelMode::Water
+                        } else {
+                            ETravelMode::Land
+                        }
+                    });
+                (points, mode)
+            }
+        }
+    }
+
+    pub fn profile_window(&mut self, ctx: &egui::Context) {
+        if !self.runtime_data.profile_open {
+            return;
+        }
+
+        let mut open = true;
+        egui::Window::new("Elevation profile")
+            .id(egui::Id::new("profile_window"))
+            .open(&mut open)
+            .default_width(400.0)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("Path:");
+                    ui.selectable_value(
+                        &mut self.runtime_data.profile_source,
+                        EProfileSource::Measure,
+                        "Measured line",
+                    );
+                    ui.add_enabled_ui(self.editor_data.enabled, |ui| {
+                        ui.selectable_value(
+                            &mut self.runtime_data.profile_source,
+                            EProfileSource::Segment,
+                            "Current segment",
+                        );
+                    });
+                });
+
+                let (path, detected_mode) = self.get_profile_path();
+                if let Some(mode) = detected_mode {
+                    self.runtime_data.profile_mode = mode;
+                }
+                ui.horizontal(|ui| {
+                    ui.label("Travel:");
+                    ui.selectable_value(
+                        &mut self.runtime_data.profile_mode,
+                        ETravelMode::Land,
+                        "Land",
+                    );
+                    ui.selectable_value(
+                        &mut self.runtime_data.profile_mode,
+                        ETravelMode::Water,
+                        "Water",
+                    );
+                    if detected_mode.is_some() {
+                        ui.label("(from route service)");
+                    }
+                });
+
+                ui.separator();
+
+                if path.len() < 2 {
+                    match self.runtime_data.profile_source {
+                        EProfileSource::Measure => {
+                            ui.label("Draw a line with the measurement tool");
+                        }
+                        EProfileSource::Segment => {
+                            ui.label("Select a segment in the editor");
+                        }
+                    }
+                    return;
+                }
+
+                let water_level = 0.0;
+                let samples =
+                    sample_elevation_profile(&self.heights, &self.dimensions, &path, 64.0);
+                let flags =
+                    get_profile_flags(&samples, self.runtime_data.profile_mode, water_level);
+                plot_elevation_profile(ui, &samples, water_level, &flags);
+
+                // problems
+                let problem = match self.runtime_data.profile_mode {
+                    ETravelMode::Water => "Crosses land",
+                    ETravelMode::Land => "Underwater",
+                };
+                if flags.is_empty() {
+                    ui.label("No problems found");
+                }
+                for (start, end) in flags.iter() {
+                    ui.colored_label(
+                        Color32::RED,
+                        format!("{}: {:.0} - {:.0} units", problem, start, end),
+                    );
+                }
+            });
+
+        if !open {
+            self.runtime_data.profile_open = false;
+        }
+    }
+}
@@ -0,0 +1,198 @@
+use std::collections::HashMap;
+
+use egui::Pos2;
+
+use crate::{overlay::regions::find_region, CellKey, TemplateApp};
+
+const MAX_RESULTS: usize = 200;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchResult {
+    pub text: String,
+    pub kind: &'static str,
+    pub key: CellKey,
+    /// exact engine position for objects
+    pub position: Option<Pos2>,
+}
+
+/// Parse grid coordinates like "-3,5" or "(-3, 5)"
+fn parse_grid(query: &str) -> Option<CellKey> {
+    let trimmed = query.trim().trim_start_matches('(').trim_end_matches(')');
+    let (x, y) = trimmed.split_once(',')?;
+    Some((x.trim().parse().ok()?, y.trim().parse().ok()?))
+}
+
+impl TemplateApp {
+    pub fn search(&self, query: &str) -> Vec<SearchResult> {
+        let mut results = vec![];
+        let query = query.trim().to_lowercase();
+        if query.is_empty() {
+            return results;
+        }
+
+        // grid coordinates
+        if let Some(key) = parse_grid(&query) {
+            results.push(SearchResult {
+                text: format!(
+                    "{:?} - {}",
+                    key,
+                    crate::get_cell_name(&self.cell_records, key)
+                ),
+                kind: "Grid",
+                key,
+                position: None,
+            });
+        }
+
+        // cell names
+        let mut keys = self.cell_records.keys().collect::<Vec<_>>();
+        keys.sort();
+        for key in keys.iter() {
+            let cell = &self.cell_records[*key];
+            if !cell.name.is_empty() && cell.name.to_lowercase().contains(&query) {
+                results.push(SearchResult {
+                    text: format!("{:?} - {}", key, cell.name),
+                    kind: "Cell",
+                    key: **key,
+                    position: None,
+                });
+            }
+        }
+
+        // regions, centered on the average of their cells
+        let mut region_cells: HashMap<String, Vec<CellKey>> = HashMap::default();
+        for key in keys.iter() {
+            if let Some(region) = &self.cell_records[*key].region {
+                region_cells
+                    .entry(region.to_lowercase())
+                    .or_default()
+                    .push(**key);
+            }
+        }
+        let mut regions = region_cells.into_iter().collect::<Vec<_>>();
+        regions.sort_by(|a, b| a.0.cmp(&b.0));
+        for (id, cells) in regions {
+            let name = find_region(&self.regn_records, &id)
+                .map(|r| r.name.clone())
+                .unwrap_or_default();
+            if !id.contains(&query) && !name.to_lowercase().contains(&query) {
+                continue;
+            }
+            let count = cells.len() as f32;
+            let x = cells.iter().map(|k| k.0 as f32).sum::<f32>() / count;
+            let y = cells.iter().map(|k| k.1 as f32).sum::<f32>() / count;
+            results.push(SearchResult {
+                text: if name.is_empty() { id } else { name },
+                kind: "Region",
+                key: (x.round() as i32, y.round() as i32),
+                position: None,
+            });
+        }
+
+        // objects
+        'outer: for key in keys.iter() {
+            let Some(references) = self.reference_records.get(*key) else {
+                continue;
+            };
+            for reference in references {
+                if results.len() >= MAX_RESULTS {
+                    break 'outer;
+                }
+                if reference.id.to_lowercase().contains(&query) {
+                    results.push(SearchResult {
+                        text: format!("{} {:?}", reference.id, key),
+                        kind: "Object",
+                        key: **key,
+                        position: Some(Pos2::new(reference.position[0], reference.position[1])),
+                    });
+                }
+            }
+        }
+
+        results.truncate(MAX_RESULTS);
+        results
+    }
+
+    /// Center the map on a search result
+    pub fn select_search_result(&mut self, result: &SearchResult) {
+        self.runtime_data.interior_view = None;
+        self.runtime_data.selected_ids = vec![result.key];
+        if let Some(position) = result.position {
+            self.focus_engine(position);
+        } else {
+            self.focus_cell(result.key);
+        }
+    }
+
+    pub fn search_window(&mut self, ctx: &egui::Context) {
+        if self.runtime_data.search_query.trim().is_empty() {
+            return;
+        }
+
+        // only search again when the query changed
+        let query = &self.runtime_data.search_query;
+        if self
+            .runtime_data
+            .search_results
+            .as_ref()
+            .is_none_or(|(cached, _)| cached != query)
+        {
+            let results = self.search(query);
+            self.runtime_data.search_results = Some((query.clone(), results));
+        }
+        let (query, results) = self.runtime_data.search_results.take().unwrap_or_default();
+        let mut open = true;
+        let mut selected = None;
+        egui::Window::new("Search")
+            .id(egui::Id::new("search_window"))
+            .open(&mut open)
+            .default_width(300.0)
+            .show(ctx, |ui| {
+                if results.is_empty() {
+                    ui.label("No results");
+                    return;
+                }
+                if results.len() >= MAX_RESULTS {
+                    ui.label(format!("Showing the first {} results", MAX_RESULTS));
+                }
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    egui::Grid::new("search_grid")
+                        .num_columns(2)
+                        .striped(true)
+                        .show(ui, |ui| {
+                            for result in results.iter() {
+                                ui.label(result.kind);
+                                let label =
+                                    egui::Label::new(&result.text).sense(egui::Sense::click());
+                                if ui.add(label).on_hover_text("Show on map").clicked() {
+                                    selected = Some(result.clone());
+                                }
+                                ui.end_row();
+                            }
+                        });
+                });
+            });
+
+        self.runtime_data.search_results = Some((query, results));
+        if let Some(result) = selected {
+            self.select_search_result(&result);
+        }
+        if !open {
+            self.runtime_data.search_query.clear();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_grid_formats() {
+        assert_eq!(parse_grid("-3,5"), Some((-3, 5)));
+        assert_eq!(parse_grid(" (-3, 5) "), Some((-3, 5)));
+        assert_eq!(parse_grid("balmora"), None);
+        assert_eq!(parse_grid("3,"), None);
+        assert_eq!(parse_grid("1.5,2"), None);
+    }
+}
@@ -1,6 +1,9 @@
 use egui::Ui;
 
-use crate::{EBackground, HeightmapSettings, LandscapeSettings, TemplateApp};
+use crate::{
+    overlay::{references::get_color_for_reference_type, travel::get_friendly_class_name},
+    EBackground, EMinimapCorner, ERefType, HeightmapSettings, LandscapeSettings, TemplateApp,
+};
 
 impl TemplateApp {
     /// Settings popup menu
@@ -63,20 +66,98 @@ impl TemplateApp {
             self.reload_paths(ctx);
         }
         ui.checkbox(&mut self.ui_data.overlay_region, "Show regions");
+        if self.ui_data.overlay_region {
+            let settings = &mut self.ui_data.region_settings;
+            ui.indent("region_settings", |ui| {
+                ui.checkbox(&mut settings.outlines_only, "Outlines only");
+                ui.add_enabled(
+                    !settings.outlines_only,
+                    egui::Slider::new(&mut settings.opacity, 0.0..=1.0).text("Opacity"),
+                );
+                ui.checkbox(&mut settings.show_names, "Region names");
+            });
+        }
         ui.checkbox(&mut self.ui_data.overlay_grid, "Show cell grid");
         ui.checkbox(&mut self.ui_data.overlay_cities, "Show cities");
+        ui.checkbox(&mut self.ui_data.overlay_references, "Show objects");
+        if self.ui_data.overlay_references {
+            ui.indent("reference_filters", |ui| {
+                for ref_type in ERefType::ALL {
+                    let color = get_color_for_reference_type(ref_type);
+                    let visible = self
+                        .ui_data
+                        .reference_filters
+                        .entry(ref_type)
+                        .or_insert(true);
+                    ui.checkbox(
+                        visible,
+                        egui::RichText::new(format!("{:?}", ref_type)).color(color),
+                    );
+                }
+            });
+        }
+        ui.checkbox(&mut self.ui_data.overlay_doors, "Show doors");
         // travel
-        let mut keys = self.travel_edges.keys().collect::<Vec<_>>();
+        let travel_colors = self.get_travel_class_colors();
+        let mut keys = self.travel_edges.keys().cloned().collect::<Vec<_>>();
         keys.sort();
         for class in keys {
-            if let Some(class_option) = self.ui_data.overlay_travel.get_mut(class) {
-                ui.checkbox(class_option, format!("\tShow travel: {}", class));
-            }
+            let name = get_friendly_class_name(&class, &self.class_names);
+            ui.horizontal(|ui| {
+                let mut color = travel_colors.get(&class).copied().unwrap_or_default();
+                if ui.color_edit_button_srgba(&mut color).changed() {
+                    self.ui_data.travel_colors.insert(class.clone(), color);
+                }
+                if let Some(class_option) = self.ui_data.overlay_travel.get_mut(&class) {
+                    ui.checkbox(class_option, format!("Show travel: {}", name))
+                        .on_hover_text(&class);
+                }
+                if self.ui_data.travel_colors.contains_key(&class)
+                    && ui.small_button("⟲").on_hover_text("Reset color").clicked()
+                {
+                    self.ui_data.travel_colors.remove(&class);
+                }
+            });
         }
 
+        ui.checkbox(&mut self.ui_data.overlay_validation, "Show cell issues");
         ui.checkbox(&mut self.ui_data.overlay_conflicts, "Show conflicts");
+        ui.checkbox(&mut self.ui_data.overlay_labels, "Show labels");
+        if self.ui_data.overlay_labels {
+            let settings = &mut self.ui_data.label_settings;
+            ui.indent("label_settings", |ui| {
+                ui.checkbox(&mut settings.show_cell_names, "Cell names");
+                ui.checkbox(&mut settings.show_region_names, "Region names");
+                ui.checkbox(&mut settings.halo, "Halo");
+                ui.checkbox(&mut settings.scale_with_zoom, "Scale with zoom");
+                ui.add(egui::Slider::new(&mut settings.font_size, 6.0..=48.0).text("Font size"));
+            });
+        }
 
         ui.checkbox(&mut self.ui_data.show_tooltips, "Show tooltips");
+        ui.checkbox(&mut self.ui_data.minimap_settings.enabled, "Show minimap");
+        if self.ui_data.minimap_settings.enabled {
+            let settings = &mut self.ui_data.minimap_settings;
+            ui.indent("minimap_settings", |ui| {
+                ui.add(egui::Slider::new(&mut settings.size, 64.0..=512.0).text("Size"));
+                egui::ComboBox::from_label("Corner")
+                    .selected_text(format!("{:?}", settings.corner))
+                    .show_ui(ui, |ui| {
+                        for corner in [
+                            EMinimapCorner::TopLeft,
+                            EMinimapCorner::TopRight,
+                            EMinimapCorner::BottomLeft,
+                            EMinimapCorner::BottomRight,
+                        ] {
+                            ui.selectable_value(
+                                &mut settings.corner,
+                                corner,
+                                format!("{:?}", corner),
+                            );
+                        }
+                    });
+            });
+        }
 
         // settings
         if self.background_handle.is_some() {
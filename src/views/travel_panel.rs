@@ -0,0 +1,212 @@
+use egui::{emath::RectTransform, Pos2};
+
+use crate::overlay::travel::get_travel_edge_at;
+use crate::travel_graph::TravelGraph;
+use crate::TemplateApp;
+
+impl TemplateApp {
+    /// Build the travel network of the loaded plugins
+    pub fn update_travel_graph(&mut self) {
+        let graph = TravelGraph::new(&self.travel_edges, &self.cell_records);
+        self.runtime_data.travel_one_way = graph.one_way_edges().into_iter().cloned().collect();
+        self.runtime_data.travel_without_incoming =
+            graph.without_incoming().into_iter().cloned().collect();
+        self.runtime_data.travel_graph = graph;
+    }
+
+    /// Travel network analysis
+    pub fn travel_panel(&mut self, ui: &mut egui::Ui, _ctx: &egui::Context) {
+        ui.heading("Travel network");
+
+        let graph = &self.runtime_data.travel_graph;
+        if graph.nodes.is_empty() {
+            ui.label("No travel services loaded");
+            return;
+        }
+
+        ui.label(format!(
+            "{} towns, {} connections",
+            graph.nodes.len(),
+            graph.edges.len()
+        ));
+
+        ui.separator();
+
+        // shortest trip
+        egui::Grid::new("travel_trip_grid")
+            .num_columns(2)
+            .show(ui, |ui| {
+                ui.label("From:");
+                egui::ComboBox::from_id_salt("travel_from")
+                    .selected_text(&self.runtime_data.travel_from)
+                    .show_ui(ui, |ui| {
+                        for node in graph.nodes.iter() {
+                            ui.selectable_value(
+                                &mut self.runtime_data.travel_from,
+                                node.clone(),
+                                node,
+                            );
+                        }
+                    });
+                ui.end_row();
+
+                ui.label("To:");
+                egui::ComboBox::from_id_salt("travel_to")
+                    .selected_text(&self.runtime_data.travel_to)
+                    .show_ui(ui, |ui| {
+                        for node in graph.nodes.iter() {
+                            ui.selectable_value(
+                                &mut self.runtime_data.travel_to,
+                                node.clone(),
+                                node,
+                            );
+                        }
+                    });
+                ui.end_row();
+            });
+
+        let from = self.runtime_data.travel_from.clone();
+        let to = self.runtime_data.travel_to.clone();
+
+        egui::ScrollArea::vertical()
+            .auto_shrink([false, true])
+            .show(ui, |ui| {
+                if graph.nodes.contains(&from) && graph.nodes.contains(&to) {
+                    match graph.shortest_path(&from, &to) {
+                        Some(path) => {
+                            ui.label(format!("Shortest trip: {} hops", path.len()));
+                            for (i, edge) in path.iter().enumerate() {
+                                ui.label(format!(
+                                    "{}. {} → {} ({}, {})",
+                                    i + 1,
+                                    edge.from,
+                                    edge.to,
+                                    edge.class,
+                                    edge.npc_id
+                                ));
+                            }
+                        }
+                        None => {
+                            ui.colored_label(egui::Color32::RED, "No connection");
+                        }
+                    }
+                }
+
+                ui.separator();
+
+                // unreachable towns
+                if graph.nodes.contains(&from) {
+                    let unreachable = graph.unreachable_from(&from);
+                    ui.collapsing(
+                        format!("Unreachable from {} ({})", from, unreachable.len()),
+                        |ui| {
+                            for node in unreachable {
+                                ui.label(node);
+                            }
+                        },
+                    );
+                }
+
+                let without_incoming = &self.runtime_data.travel_without_incoming;
+                ui.collapsing(
+                    format!("Towns without incoming travel ({})", without_incoming.len()),
+                    |ui| {
+                        for node in without_incoming {
+                            ui.label(node);
+                        }
+                    },
+                );
+
+                // one way connections
+                let one_way = &self.runtime_data.travel_one_way;
+                ui.collapsing(format!("One-way connections ({})", one_way.len()), |ui| {
+                    for edge in one_way {
+                        ui.label(format!(
+                            "{} → {} ({}, {})",
+                            edge.from, edge.to, edge.class, edge.npc_id
+                        ));
+                    }
+                });
+            });
+    }
+
+    /// Window with the details of the selected travel edge
+    pub fn travel_window(&mut self, ctx: &egui::Context) {
+        let Some(edge) = self.runtime_data.selected_travel_edge.clone() else {
+            return;
+        };
+
+        let mut open = true;
+        egui::Window::new("Travel")
+            .id(egui::Id::new("travel_window"))
+            .open(&mut open)
+            .resizable(false)
+            .show(ctx, |ui| {
+                egui::Grid::new("travel_grid")
+                    .num_columns(2)
+                    .show(ui, |ui| {
+                        ui.label("NPC:");
+                        ui.label(format!("{} ({})", edge.npc_name, edge.npc_id));
+                        ui.end_row();
+
+                        ui.label("Class:");
+                        ui.label(&edge.class);
+                        ui.end_row();
+
+                        ui.label("Start:");
+                        ui.label(format!(
+                            "{} ({:.0}, {:.0}, {:.0})",
+                            crate::get_cell_name(&self.cell_records, edge.start_cell()),
+                            edge.start[0],
+                            edge.start[1],
+                            edge.start[2]
+                        ));
+                        ui.end_row();
+
+                        if let Some(interior) = &edge.npc_interior {
+                            ui.label("Start interior:");
+                            ui.label(interior);
+                            ui.end_row();
+                        }
+
+                        ui.label("Destination:");
+                        ui.label(format!(
+                            "{} ({:.0}, {:.0}, {:.0})",
+                            crate::get_cell_name(&self.cell_records, edge.destination_cell()),
+                            edge.destination[0],
+                            edge.destination[1],
+                            edge.destination[2]
+                        ));
+                        ui.end_row();
+
+                        if let Some(interior) = &edge.interior {
+                            ui.label("Destination interior:");
+                            ui.label(interior);
+                            ui.end_row();
+                        }
+                    });
+            });
+
+        if !open {
+            self.runtime_data.selected_travel_edge = None;
+        }
+    }
+
+    pub fn travel_on_click(&mut self, from_screen: RectTransform, interact_pos: Pos2) -> bool {
+        let edge = get_travel_edge_at(
+            from_screen.inverse(),
+            &self.dimensions,
+            &self.travel_edges,
+            &self.ui_data.overlay_travel,
+            interact_pos,
+            4.0,
+        );
+
+        if let Some(edge) = edge {
+            self.runtime_data.selected_travel_edge = Some(edge.clone());
+            return true;
+        }
+
+        false
+    }
+}